@@ -0,0 +1,20 @@
+#![no_main]
+
+use bigdecimal::BigDecimal;
+use console_calculator::{evaluate, EvalContext, FloatPolicy, Number, Rational};
+use libfuzzer_sys::fuzz_target;
+
+fn eval<N: Number>(source: &str, policy: FloatPolicy) {
+    let mut context = EvalContext::<N>::new();
+    context.set_policy(policy);
+    let _ = evaluate(source, &mut context);
+}
+
+// Evaluation never panics, whatever the number type and policy.
+fuzz_target!(|source: &str| {
+    for policy in [FloatPolicy::Ieee, FloatPolicy::Strict] {
+        eval::<f64>(source, policy);
+        eval::<BigDecimal>(source, policy);
+        eval::<Rational>(source, policy);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use console_calculator::ExpressionBuilder;
+use libfuzzer_sys::fuzz_target;
+
+// Parsing never panics, and a parsed tree displays as source parsing back to the same tree.
+fuzz_target!(|source: &str| {
+    let Ok(expression) = ExpressionBuilder::<f64>::new(source).get_expression() else {
+        return;
+    };
+    let displayed = expression.to_string();
+    let reparsed = ExpressionBuilder::<f64>::new(&displayed).get_expression();
+    assert_eq!(reparsed.map(|expression| expression.to_string()), Ok(displayed));
+});
@@ -0,0 +1,117 @@
+use std::{error::Error, f64::consts, fmt::Display, str::FromStr};
+
+use crate::number::{Number, NumberError};
+
+/// Unit of the angles trigonometric functions take and return, and of suffixes such as `90deg`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum AngleUnit {
+    #[default]
+    Radians,
+    Degrees,
+    /// A right angle is 100 gradians.
+    Gradians,
+}
+
+impl AngleUnit {
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleUnit::Radians => angle,
+            AngleUnit::Degrees => angle.to_radians(),
+            AngleUnit::Gradians => angle * consts::PI / 200.0,
+        }
+    }
+
+    pub fn from_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleUnit::Radians => angle,
+            AngleUnit::Degrees => angle.to_degrees(),
+            AngleUnit::Gradians => angle * 200.0 / consts::PI,
+        }
+    }
+
+    /// Converts an angle in this unit to `unit`. Only floats can convert between different units,
+    /// exact number types keep an angle already in `unit` as it is.
+    pub fn convert<N: Number>(self, angle: N, unit: AngleUnit) -> Result<N, NumberError> {
+        if self == unit {
+            return Ok(angle);
+        }
+        N::from_f64(unit.from_radians(self.to_radians(angle.to_f64())))
+    }
+}
+
+impl FromStr for AngleUnit {
+    type Err = AngleUnitError;
+
+    fn from_str(unit: &str) -> Result<Self, Self::Err> {
+        match unit {
+            "radians" | "rad" => Ok(AngleUnit::Radians),
+            "degrees" | "deg" => Ok(AngleUnit::Degrees),
+            "gradians" | "grad" => Ok(AngleUnit::Gradians),
+            unit => Err(AngleUnitError::UnknownUnit(unit.to_string())),
+        }
+    }
+}
+
+/// Writes the suffix marking an angle in the unit, e.g. `deg`.
+impl Display for AngleUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AngleUnit::Radians => write!(f, "rad"),
+            AngleUnit::Degrees => write!(f, "deg"),
+            AngleUnit::Gradians => write!(f, "grad"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AngleUnitError {
+    UnknownUnit(String),
+}
+
+impl Display for AngleUnitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AngleUnitError::UnknownUnit(unit) => {
+                write!(f, "Unknown angle unit {}, expected deg, rad or grad.", unit)
+            }
+        }
+    }
+}
+
+impl Error for AngleUnitError {}
+
+#[cfg(test)]
+mod angle_tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    #[test]
+    fn radians() {
+        assert_eq!(AngleUnit::Radians.to_radians(1.0), 1.0);
+        assert_eq!(AngleUnit::Degrees.to_radians(180.0), consts::PI);
+        assert_eq!(AngleUnit::Gradians.to_radians(200.0), consts::PI);
+        assert_eq!(AngleUnit::Degrees.from_radians(consts::PI), 180.0);
+        assert_eq!(AngleUnit::Gradians.from_radians(consts::PI), 200.0);
+    }
+
+    #[test]
+    fn convert() {
+        assert_eq!(AngleUnit::Degrees.convert(90.0, AngleUnit::Radians), Ok(consts::FRAC_PI_2));
+        assert_eq!(AngleUnit::Gradians.convert(100.0, AngleUnit::Degrees), Ok(90.0));
+        let decimal = "90".parse::<BigDecimal>().unwrap();
+        assert_eq!(AngleUnit::Degrees.convert(decimal.clone(), AngleUnit::Degrees), Ok(decimal.clone()));
+        assert_eq!(AngleUnit::Degrees.convert(decimal, AngleUnit::Radians), Err(NumberError::Inexact));
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("deg".parse(), Ok(AngleUnit::Degrees));
+        assert_eq!("radians".parse(), Ok(AngleUnit::Radians));
+        assert_eq!("grad".parse(), Ok(AngleUnit::Gradians));
+        assert_eq!(
+            "turn".parse::<AngleUnit>(),
+            Err(AngleUnitError::UnknownUnit("turn".to_string()))
+        );
+        assert_eq!(AngleUnit::Gradians.to_string(), "grad");
+    }
+}
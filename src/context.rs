@@ -0,0 +1,241 @@
+use std::{collections::HashMap, f64::consts};
+
+use crate::{angle::AngleUnit, function::UserFunction, number::Number};
+
+const CONSTANTS: [(&str, f64); 2] = [("pi", consts::PI), ("e", consts::E)];
+/// Most user function calls nested at once, low enough that a simple recursion such as
+/// `f(x) = f(x)+1` reaches it before `MAX_DEPTH`.
+pub const MAX_CALL_DEPTH: usize = 100;
+/// Deepest nesting of operations and calls evaluated at once, keeping evaluation off the end of
+/// the stack for trees the builder didn't produce. It takes under 1MB of stack in a debug build,
+/// so evaluation fits the 2MB a spawned thread gets by default.
+pub const MAX_DEPTH: usize = 256;
+
+/// How floating point results that aren't ordinary numbers are handled.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum FloatPolicy {
+    /// `1/0` is `inf` and `0/0` is `NaN`, as IEEE 754 defines them.
+    #[default]
+    Ieee,
+    /// Division by zero, overflow to infinity and NaN are reported as errors.
+    Strict,
+}
+
+/// Variables, functions and settings that outlive a single evaluation, e.g. between lines of the
+/// REPL. Constants the number type can't represent exactly are left out, the number type itself
+/// being the numeric mode. See [`MAX_DEPTH`] for the stack evaluation needs.
+#[derive(Debug, Clone)]
+pub struct EvalContext<N = f64> {
+    variables: HashMap<String, N>,
+    functions: HashMap<String, UserFunction<N>>,
+    call_depth: usize,
+    depth: usize,
+    policy: FloatPolicy,
+    angle_unit: AngleUnit,
+    max_call_depth: usize,
+    max_depth: usize,
+}
+
+impl<N: Number> EvalContext<N> {
+    pub fn new() -> Self {
+        Self {
+            variables: CONSTANTS
+                .iter()
+                .filter_map(|(identifier, value)| {
+                    Some((identifier.to_string(), N::from_f64(*value).ok()?))
+                })
+                .collect(),
+            functions: HashMap::new(),
+            call_depth: 0,
+            depth: 0,
+            policy: FloatPolicy::default(),
+            angle_unit: AngleUnit::default(),
+            max_call_depth: MAX_CALL_DEPTH,
+            max_depth: MAX_DEPTH,
+        }
+    }
+
+    pub fn get(&self, identifier: &str) -> Option<N> {
+        self.variables.get(identifier).cloned()
+    }
+
+    pub fn set(&mut self, identifier: &str, value: N) {
+        self.variables.insert(identifier.to_string(), value);
+    }
+
+    pub(crate) fn variable_names(&self) -> impl Iterator<Item = &String> {
+        self.variables.keys()
+    }
+
+    pub(crate) fn function_names(&self) -> impl Iterator<Item = &String> {
+        self.functions.keys()
+    }
+
+    pub(crate) fn function(&self, identifier: &str) -> Option<&UserFunction<N>> {
+        self.functions.get(identifier)
+    }
+
+    pub(crate) fn define(&mut self, identifier: &str, function: UserFunction<N>) {
+        self.functions.insert(identifier.to_string(), function);
+    }
+
+    /// Also decides whether division by zero is an error.
+    pub fn policy(&self) -> FloatPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: FloatPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn angle_unit(&self) -> AngleUnit {
+        self.angle_unit
+    }
+
+    pub fn set_angle_unit(&mut self, angle_unit: AngleUnit) {
+        self.angle_unit = angle_unit;
+    }
+
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// Limits how many user function calls may be nested, `MAX_CALL_DEPTH` by default.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Limits how deeply expressions may be nested while evaluating, `MAX_DEPTH` by default.
+    /// Raising it needs a proportionally larger stack, about 4KB per level in a debug build.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Registers a user function call, returning false once `max_call_depth` calls are nested.
+    pub(crate) fn enter_call(&mut self) -> bool {
+        if self.call_depth >= self.max_call_depth {
+            return false;
+        }
+        self.call_depth += 1;
+        true
+    }
+
+    /// Unregisters the innermost user function call once it has returned.
+    pub(crate) fn exit_call(&mut self) {
+        self.call_depth = self.call_depth.saturating_sub(1);
+    }
+
+    /// Registers an expression being evaluated, returning false once `max_depth` are nested.
+    pub(crate) fn enter(&mut self) -> bool {
+        if self.depth >= self.max_depth {
+            return false;
+        }
+        self.depth += 1;
+        true
+    }
+
+    /// Unregisters the innermost expression once it has been evaluated, whether or not it failed.
+    pub(crate) fn exit(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Whether every call and expression entered has been exited again.
+    #[cfg(test)]
+    pub(crate) fn is_unwound(&self) -> bool {
+        self.depth == 0 && self.call_depth == 0
+    }
+}
+
+impl<N: Number> Default for EvalContext<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod context_tests {
+    use super::*;
+    use crate::{
+        expression::{Expression, ExpressionKind},
+        span::Span,
+    };
+
+    #[test]
+    fn get_set() {
+        let mut context = EvalContext::new();
+        assert_eq!(context.get("pi"), Some(consts::PI));
+        assert_eq!(context.get("x"), None);
+        context.set("x", 1.0);
+        assert_eq!(context.get("x"), Some(1.0));
+        context.set("x", 2.0);
+        assert_eq!(context.get("x"), Some(2.0));
+        assert_eq!(
+            EvalContext::<f32>::new().get("pi"),
+            Some(std::f32::consts::PI)
+        );
+    }
+
+    #[test]
+    fn define() {
+        let mut context = EvalContext::new();
+        let function = UserFunction::new(
+            vec![],
+            Expression::new(ExpressionKind::Literal(1.0), Span::new(0, 1)),
+        );
+        assert_eq!(context.function("f"), None);
+        context.define("f", function.clone());
+        assert_eq!(context.function("f"), Some(&function));
+        assert_eq!(context.get("f"), None);
+    }
+
+    #[test]
+    fn policy() {
+        let mut context = EvalContext::<f64>::new();
+        assert_eq!(context.policy(), FloatPolicy::Ieee);
+        context.set_policy(FloatPolicy::Strict);
+        assert_eq!(context.policy(), FloatPolicy::Strict);
+    }
+
+    #[test]
+    fn call_depth() {
+        let mut context = EvalContext::<f64>::new();
+        for _ in 0..MAX_CALL_DEPTH {
+            assert!(context.enter_call());
+        }
+        assert!(!context.enter_call());
+        context.exit_call();
+        assert!(context.enter_call());
+        let mut context = EvalContext::<f64>::new();
+        context.set_max_call_depth(0);
+        assert_eq!(context.max_call_depth(), 0);
+        assert!(!context.enter_call());
+    }
+
+    #[test]
+    fn depth() {
+        let mut context = EvalContext::<f64>::new();
+        for _ in 0..MAX_DEPTH {
+            assert!(context.enter());
+        }
+        assert!(!context.enter());
+        context.exit();
+        assert!(context.enter());
+        let mut context = EvalContext::<f64>::new();
+        context.set_max_depth(1);
+        assert_eq!(context.max_depth(), 1);
+        assert!(context.enter());
+        assert!(!context.enter());
+    }
+
+    #[test]
+    fn angle_unit() {
+        let mut context = EvalContext::<f64>::new();
+        assert_eq!(context.angle_unit(), AngleUnit::Radians);
+        context.set_angle_unit(AngleUnit::Degrees);
+        assert_eq!(context.angle_unit(), AngleUnit::Degrees);
+    }
+}
@@ -0,0 +1,269 @@
+use bigdecimal::{
+    num_bigint::BigInt, BigDecimal, One, RoundingMode, Signed, ToPrimitive, Zero,
+};
+use num_integer::Integer;
+
+use crate::number::{Number, NumberError, MAX_POWER_DIGITS};
+
+/// Arbitrary precision decimals. Every operation is exact and fails with `NumberError::Inexact`
+/// when the result has no finite decimal expansion, e.g. `1/3`.
+impl Number for BigDecimal {
+    const EXACT: bool = true;
+
+    fn add(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(self + other)
+    }
+
+    fn subtract(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(self - other)
+    }
+
+    fn multiply(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(self * other)
+    }
+
+    fn divide(&self, other: &Self) -> Result<Self, NumberError> {
+        if Zero::is_zero(other) {
+            return Err(NumberError::DivisionByZero);
+        }
+        // self / other = (numerator / denominator) * 10^-scale
+        let (numerator, numerator_scale) = self.as_bigint_and_exponent();
+        let (denominator, denominator_scale) = other.as_bigint_and_exponent();
+        let divisor = numerator.gcd(&denominator) * denominator.signum();
+        let (mut numerator, mut denominator) = (numerator / &divisor, denominator / &divisor);
+        let mut scale = numerator_scale - denominator_scale;
+        // The quotient only terminates if the denominator's prime factors are all 2s and 5s,
+        // each of which can be turned into a power of ten.
+        while !denominator.is_one() {
+            if denominator.is_multiple_of(&BigInt::from(10)) {
+                denominator /= 10;
+            } else if denominator.is_even() {
+                denominator /= 2;
+                numerator *= 5;
+            } else if denominator.is_multiple_of(&BigInt::from(5)) {
+                denominator /= 5;
+                numerator *= 2;
+            } else {
+                return Err(NumberError::Inexact);
+            }
+            scale += 1;
+        }
+        Ok(BigDecimal::new(numerator, scale))
+    }
+
+    fn remainder(&self, other: &Self) -> Result<Self, NumberError> {
+        if Zero::is_zero(other) {
+            return Err(NumberError::DivisionByZero);
+        }
+        Ok(self % other)
+    }
+
+    fn power(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if !exponent.is_integer() {
+            return Err(NumberError::Inexact);
+        }
+        let exponent = exponent.to_i32().ok_or(NumberError::Inexact)?;
+        // Digits both significant and implied by the scale, as in `1e100`, are multiplied.
+        let digits = self.digits() + self.fractional_digit_count().unsigned_abs();
+        if self.abs() != BigDecimal::one()
+            && !Zero::is_zero(self)
+            && digits.saturating_mul(exponent.unsigned_abs().into()) > MAX_POWER_DIGITS
+        {
+            return Err(NumberError::OutOfRange);
+        }
+        let mut result = BigDecimal::one();
+        let mut base = self.clone();
+        let mut remaining = exponent.unsigned_abs();
+        while remaining > 0 {
+            if remaining % 2 == 1 {
+                result = &result * &base;
+            }
+            base = base.square();
+            remaining /= 2;
+        }
+        if exponent.is_negative() {
+            return BigDecimal::one().divide(&result);
+        }
+        Ok(result)
+    }
+
+    fn negate(&self) -> Self {
+        -self
+    }
+
+    fn abs(&self) -> Self {
+        BigDecimal::abs(self)
+    }
+
+    fn floor(&self) -> Self {
+        self.with_scale_round(0, RoundingMode::Floor)
+    }
+
+    fn ceil(&self) -> Self {
+        self.with_scale_round(0, RoundingMode::Ceiling)
+    }
+
+    fn round(&self) -> Self {
+        self.with_scale_round(0, RoundingMode::HalfUp)
+    }
+
+    fn from_f64(_: f64) -> Result<Self, NumberError> {
+        Err(NumberError::Inexact)
+    }
+
+    fn to_f64(&self) -> f64 {
+        ToPrimitive::to_f64(self).unwrap_or(f64::NAN)
+    }
+
+    fn to_integer(&self) -> Option<BigInt> {
+        self.is_integer()
+            .then(|| self.with_scale(0).into_bigint_and_exponent().0)
+    }
+
+    fn from_integer(integer: BigInt) -> Result<Self, NumberError> {
+        Ok(BigDecimal::from(integer))
+    }
+
+    fn is_zero(&self) -> bool {
+        Zero::is_zero(self)
+    }
+
+    /// Written from the digits themselves, so `10^400` keeps every digit of its mantissa.
+    fn to_scientific(&self) -> String {
+        let (digits, scale) = self.normalized().into_bigint_and_exponent();
+        let sign = if digits.is_negative() { "-" } else { "" };
+        let digits = digits.magnitude().to_string();
+        let exponent = digits.len() as i64 - 1 - scale;
+        match digits.split_at(1) {
+            (first, "") => format!("{}{}e{}", sign, first, exponent),
+            (first, rest) => format!("{}{}.{}e{}", sign, first, rest, exponent),
+        }
+    }
+
+    fn root(&self, degree: u32) -> Result<Self, NumberError> {
+        if self.is_negative() && degree.is_multiple_of(2) {
+            return Err(NumberError::OutOfRange);
+        }
+        // The root of digits * 10^-scale, with trailing zeros added until the scale divides.
+        let (mut digits, mut scale) = self.as_bigint_and_exponent();
+        while scale % i64::from(degree) != 0 {
+            digits *= 10;
+            scale += 1;
+        }
+        let root = digits.nth_root(degree);
+        if root.pow(degree) != digits {
+            return Err(NumberError::Inexact);
+        }
+        Ok(BigDecimal::new(root, scale / i64::from(degree)))
+    }
+
+    /// Unlike `divide` this always has an exact result, `1//3` is `0`.
+    fn floor_divide(&self, other: &Self) -> Result<Self, NumberError> {
+        let remainder = self.remainder(other)?;
+        // Removing the remainder leaves a multiple of `other`, so the division terminates.
+        let quotient = (self - &remainder).divide(other)?;
+        if !Zero::is_zero(&remainder) && remainder.is_negative() != other.is_negative() {
+            return Ok(quotient - BigDecimal::one());
+        }
+        Ok(quotient)
+    }
+}
+
+#[cfg(test)]
+mod decimal_tests {
+    use super::*;
+
+    fn decimal(number: &str) -> BigDecimal {
+        number.parse().unwrap()
+    }
+
+    #[test]
+    fn exact_arithmetic() {
+        assert_eq!(decimal("0.1").add(&decimal("0.2")), Ok(decimal("0.3")));
+        assert_eq!(decimal("0.3").subtract(&decimal("0.1")), Ok(decimal("0.2")));
+        assert_eq!(decimal("1.1").multiply(&decimal("1.1")), Ok(decimal("1.21")));
+        assert_eq!(decimal("7.5").remainder(&decimal("2")), Ok(decimal("1.5")));
+        assert_eq!(
+            decimal("12345678901234567890").add(&decimal("1")),
+            Ok(decimal("12345678901234567891"))
+        );
+    }
+
+    #[test]
+    fn floor_divide() {
+        assert_eq!(decimal("1").floor_divide(&decimal("3")), Ok(decimal("0")));
+        assert_eq!(decimal("-1").floor_divide(&decimal("3")), Ok(decimal("-1")));
+        assert_eq!(decimal("7.5").floor_divide(&decimal("-0.7")), Ok(decimal("-11")));
+        assert_eq!(decimal("1").floor_divide(&decimal("0")), Err(NumberError::DivisionByZero));
+    }
+
+    #[test]
+    fn divide() {
+        assert_eq!(decimal("1").divide(&decimal("4")), Ok(decimal("0.25")));
+        assert_eq!(decimal("-1").divide(&decimal("8")), Ok(decimal("-0.125")));
+        assert_eq!(decimal("1").divide(&decimal("-0.2")), Ok(decimal("-5")));
+        assert_eq!(decimal("0.03").divide(&decimal("0.6")), Ok(decimal("0.05")));
+        assert_eq!(decimal("300").divide(&decimal("1.5")), Ok(decimal("200")));
+        assert_eq!(decimal("1").divide(&decimal("3")), Err(NumberError::Inexact));
+        assert_eq!(decimal("1").divide(&decimal("0")), Err(NumberError::DivisionByZero));
+        assert_eq!(decimal("1").remainder(&decimal("0")), Err(NumberError::DivisionByZero));
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(decimal("1.5").power(&decimal("2")), Ok(decimal("2.25")));
+        assert_eq!(decimal("2").power(&decimal("-2")), Ok(decimal("0.25")));
+        assert_eq!(decimal("10").power(&decimal("0")), Ok(decimal("1")));
+        assert_eq!(
+            decimal("2").power(&decimal("100")),
+            Ok(decimal("1267650600228229401496703205376"))
+        );
+        assert_eq!(decimal("3").power(&decimal("-1")), Err(NumberError::Inexact));
+        assert_eq!(decimal("4").power(&decimal("0.5")), Err(NumberError::Inexact));
+        assert_eq!(decimal("-1").power(&decimal("2000000001")), Ok(decimal("-1")));
+        assert_eq!(decimal("9").power(&decimal("387420489")), Err(NumberError::OutOfRange));
+        assert_eq!(decimal("1e1000").power(&decimal("10000")), Err(NumberError::OutOfRange));
+    }
+
+    #[test]
+    fn to_scientific() {
+        assert_eq!(decimal("6.02e23").to_scientific(), "6.02e23");
+        assert_eq!(decimal("-0.00012300").to_scientific(), "-1.23e-4");
+        assert_eq!(decimal("0").to_scientific(), "0e0");
+        assert_eq!(decimal("7").to_scientific(), "7e0");
+        assert_eq!(decimal("1e400").to_scientific(), "1e400");
+        assert_eq!(
+            decimal("12345678901234567890.5").to_scientific(),
+            "1.23456789012345678905e19"
+        );
+    }
+
+    #[test]
+    fn root() {
+        assert_eq!(decimal("4").root(2), Ok(decimal("2")));
+        assert_eq!(decimal("0.0144").root(2), Ok(decimal("0.12")));
+        assert_eq!(decimal("1e6").root(2), Ok(decimal("1000")));
+        assert_eq!(decimal("0.001").root(3), Ok(decimal("0.1")));
+        assert_eq!(decimal("-27").root(3), Ok(decimal("-3")));
+        assert_eq!(decimal("0").root(2), Ok(decimal("0")));
+        assert_eq!(decimal("2").root(2), Err(NumberError::Inexact));
+        assert_eq!(decimal("0.1").root(2), Err(NumberError::Inexact));
+        assert_eq!(decimal("-4").root(2), Err(NumberError::OutOfRange));
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(Number::floor(&decimal("-1.5")), decimal("-2"));
+        assert_eq!(Number::ceil(&decimal("-1.5")), decimal("-1"));
+        assert_eq!(Number::round(&decimal("2.5")), decimal("3"));
+        assert_eq!(Number::abs(&decimal("-2.5")), decimal("2.5"));
+        assert_eq!(decimal("2.50").multiply(&decimal("2")).unwrap().to_integer(), Some(BigInt::from(5)));
+        assert_eq!(decimal("1e2").to_integer(), Some(BigInt::from(100)));
+        assert_eq!(decimal("2.5").to_integer(), None);
+    }
+
+    #[test]
+    fn from_f64() {
+        assert_eq!(<BigDecimal as Number>::from_f64(0.5), Err(NumberError::Inexact));
+    }
+}
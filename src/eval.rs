@@ -1,88 +1,416 @@
 use std::{error::Error, fmt::Display};
 
 use crate::{
+    context::{EvalContext, FloatPolicy},
     expression_builder::{ExpressionBuilder, ExpressionBuilderError},
-    expression_item::ExpressionItem,
-    operator::Operator,
+    expression::{Expression, ExpressionKind},
+    function::{Arity, Builtin, UserFunction},
+    number::{Number, NumberError},
+    operator::{Operator, UnaryOperator},
+    span::Span,
+    statement::{Statement, StatementKind},
+    value::Value,
 };
 
-pub trait Evaluate {
-    fn eval(&self) -> Result<Option<f32>, EvalError>;
+pub trait Evaluate<N: Number> {
+    fn eval(&self, context: &mut EvalContext<N>) -> Result<Option<Value<N>>, EvalError>;
 }
 
-impl Evaluate for ExpressionItem {
-    fn eval(&self) -> Result<Option<f32>, EvalError> {
-        match self {
-            ExpressionItem::Operand(operand) => Ok(Some(*operand)),
-            ExpressionItem::Operator(_) => Err(EvalError::ExpectedOperand),
-            ExpressionItem::Parentheses(parentheses) => {
-                const OPERATIONS_ORDER: [&[char]; 3] = [&['^'], &['*', '/', '%'], &['+', '-']];
-                let mut expression = *parentheses.clone();
-                for operations in OPERATIONS_ORDER {
-                    let (first, rest) = match expression.split_first().unzip() {
-                        (None, None) => return Ok(None),
-                        (None, Some(_)) => unreachable!(),
-                        (Some(first), None) => return first.eval(),
-                        (Some(first), Some(rest)) => (first, rest),
-                    };
-                    expression = rest.chunks(2).fold(
-                        Ok(vec![first.clone()]),
-                        |acc: Result<Vec<ExpressionItem>, EvalError>, chunk: &[ExpressionItem]| {
-                            let mut acc = acc?;
-                            let left_operand = acc
-                                .pop()
-                                .unwrap().eval()?
-                                .ok_or(EvalError::ExpectedOperand)?;
-                            let operator = chunk
-                                .get(0)
-                                .unwrap()
-                                .operator()
-                                .ok_or(EvalError::ExpectedOperator)?;
-                            let right_operand = chunk
-                                .get(1)
-                                .ok_or(EvalError::ExpectedOperand)?
-                                .eval()?
-                                .ok_or(EvalError::ExpectedOperand)?;
-                            if operations.contains(&char::from(&operator)) {
-                                acc.push(ExpressionItem::from(&match operator {
-                                    Operator::Power => left_operand.powf(right_operand),
-                                    Operator::Multiply => left_operand * right_operand,
-                                    Operator::Divide => left_operand / right_operand,
-                                    Operator::Remainder => left_operand % right_operand,
-                                    Operator::Add => left_operand + right_operand,
-                                    Operator::Subtract => left_operand - right_operand,
-                                }));
-                            } else {
-                                acc.append(&mut vec![
-                                    ExpressionItem::from(&left_operand),
-                                    ExpressionItem::from(operator),
-                                    ExpressionItem::from(&right_operand),
-                                ]);
-                            }
-                            Ok(acc)
-                        },
-                    )?;
+/// The number `value` holds, `span` covering the source that produced it.
+fn number<N>(value: Value<N>, span: Span) -> Result<N, EvalError> {
+    value.number().ok_or(EvalError::ExpectedNumber(span))
+}
+
+fn boolean<N>(value: Value<N>, span: Span) -> Result<bool, EvalError> {
+    value.boolean().ok_or(EvalError::ExpectedBoolean(span))
+}
+
+/// Applies an operator to two numbers, comparisons giving a boolean.
+fn apply<N: Number>(
+    left: &N,
+    operator: &Operator,
+    operator_span: Span,
+    right: &N,
+) -> Result<Value<N>, EvalError> {
+    let result = match operator {
+        Operator::Power => left.power(right),
+        Operator::Multiply => left.multiply(right),
+        Operator::Divide => left.divide(right),
+        Operator::Remainder => left.remainder(right),
+        Operator::Add => left.add(right),
+        Operator::Subtract => left.subtract(right),
+        Operator::FloorDivide => left.floor_divide(right),
+        Operator::BitAnd => left.bit_and(right),
+        Operator::BitOr => left.bit_or(right),
+        Operator::BitXor => left.bit_xor(right),
+        Operator::ShiftLeft => left.shift_left(right),
+        Operator::ShiftRight => left.shift_right(right),
+        Operator::Less => return Ok(Value::Boolean(left < right)),
+        Operator::LessEqual => return Ok(Value::Boolean(left <= right)),
+        Operator::Equal => return Ok(Value::Boolean(left == right)),
+        Operator::NotEqual => return Ok(Value::Boolean(left != right)),
+        Operator::Greater => return Ok(Value::Boolean(left > right)),
+        Operator::GreaterEqual => return Ok(Value::Boolean(left >= right)),
+        Operator::And | Operator::Or => return Err(EvalError::ExpectedBoolean(operator_span)),
+    };
+    Ok(Value::Number(result.map_err(|err| EvalError::NumberError(err, operator_span))?))
+}
+
+/// With the strict policy, turns a number IEEE floats represent as infinity or NaN into an error
+/// pointing at `span`, the source that produced it.
+fn check<N: Number>(
+    value: Value<N>,
+    span: Span,
+    context: &EvalContext<N>,
+) -> Result<Value<N>, EvalError> {
+    match &value {
+        _ if context.policy() == FloatPolicy::Ieee => Ok(value),
+        Value::Number(number) if number.is_nan() => Err(EvalError::NotANumber(span)),
+        Value::Number(number) if number.is_infinite() => Err(EvalError::Overflow(span)),
+        _ => Ok(value),
+    }
+}
+
+/// `span` covers the operator and its operand. An angle is converted to the context's unit.
+fn evaluate_unary<N: Number>(
+    operator: &UnaryOperator,
+    operand: &Expression<N>,
+    span: Span,
+    context: &mut EvalContext<N>,
+) -> Result<Value<N>, EvalError> {
+    let length = operator.to_string().len();
+    let operator_span = if operator.is_postfix() {
+        Span::new(span.end.saturating_sub(length), span.end)
+    } else {
+        Span::new(span.start, span.start.saturating_add(1))
+    };
+    let operand_span = operand.span;
+    // An integer operator points at its operand when it has a fractional part.
+    let at_operator = |err| match err {
+        NumberError::NotAnInteger => EvalError::NumberError(err, operand_span),
+        err => EvalError::NumberError(err, operator_span),
+    };
+    let operand = evaluate(operand, context)?;
+    let value = match operator {
+        UnaryOperator::Plus => Value::Number(number(operand, operand_span)?),
+        UnaryOperator::Negate => Value::Number(number(operand, operand_span)?.negate()),
+        UnaryOperator::BitNot => {
+            Value::Number(number(operand, operand_span)?.bit_not().map_err(at_operator)?)
+        }
+        UnaryOperator::Factorial => {
+            Value::Number(number(operand, operand_span)?.factorial().map_err(at_operator)?)
+        }
+        UnaryOperator::Not => Value::Boolean(!boolean(operand, operand_span)?),
+        UnaryOperator::Angle(unit) => Value::Number(
+            unit.convert(number(operand, operand_span)?, context.angle_unit())
+                .map_err(at_operator)?,
+        ),
+    };
+    check(value, operator_span, context)
+}
+
+/// Evaluates a chain of operations nesting to the left, as `1+2+3` does, in a loop. Only the
+/// right operands recurse, so the length of a chain isn't limited by the depth.
+fn evaluate_binary<N: Number>(
+    expression: &Expression<N>,
+    context: &mut EvalContext<N>,
+) -> Result<Value<N>, EvalError> {
+    let mut chain = Vec::new();
+    let mut first = expression;
+    while let ExpressionKind::Binary(left, operator, operator_span, right) = &first.kind {
+        chain.push((first.span, operator, *operator_span, right));
+        first = left;
+    }
+    let mut value = evaluate(first, context)?;
+    let mut left_span = first.span;
+    for (span, operator, operator_span, right) in chain.into_iter().rev() {
+        value = evaluate_operation(value, left_span, operator, operator_span, right, context)?;
+        left_span = span;
+    }
+    Ok(value)
+}
+
+/// Applies `operator` to the already evaluated `left` operand, which covers `left_span`.
+fn evaluate_operation<N: Number>(
+    left: Value<N>,
+    left_span: Span,
+    operator: &Operator,
+    operator_span: Span,
+    right: &Expression<N>,
+    context: &mut EvalContext<N>,
+) -> Result<Value<N>, EvalError> {
+    if matches!(operator, Operator::And | Operator::Or) {
+        // The right side is only evaluated if the left one doesn't already decide the result.
+        let decided = boolean(left, left_span)?;
+        if decided == (*operator == Operator::Or) {
+            return Ok(Value::Boolean(decided));
+        }
+        return Ok(Value::Boolean(boolean(evaluate(right, context)?, right.span)?));
+    }
+    let right_span = right.span;
+    match (left, evaluate(right, context)?) {
+        (Value::Boolean(left), Value::Boolean(right)) => match operator {
+            Operator::Equal => Ok(Value::Boolean(left == right)),
+            Operator::NotEqual => Ok(Value::Boolean(left != right)),
+            _ => Err(EvalError::ExpectedNumber(left_span)),
+        },
+        (left, right) => {
+            let (left, right) = (number(left, left_span)?, number(right, right_span)?);
+            let strict = context.policy() == FloatPolicy::Strict;
+            if strict
+                && matches!(
+                    operator,
+                    Operator::Divide | Operator::FloorDivide | Operator::Remainder
+                )
+                && right.is_zero()
+            {
+                return Err(EvalError::DivisionByZero(operator_span));
+            }
+            match apply(&left, operator, operator_span, &right) {
+                Err(EvalError::NumberError(NumberError::DivisionByZero, _)) if strict => {
+                    Err(EvalError::DivisionByZero(operator_span))
+                }
+                // An integer operator points at the operand with a fractional part.
+                Err(EvalError::NumberError(NumberError::NotAnInteger, _)) => {
+                    let span = if left.to_integer().is_none() { left_span } else { right_span };
+                    Err(EvalError::NumberError(NumberError::NotAnInteger, span))
                 }
-                
-                expression.first().unwrap().eval()
+                result => check(result?, operator_span, context),
             }
         }
     }
 }
 
+/// Calls a user function, which takes precedence over a builtin of the same name. `span` covers
+/// the whole call.
+fn evaluate_call<N: Number>(
+    identifier: &str,
+    arguments: &[Expression<N>],
+    span: Span,
+    context: &mut EvalContext<N>,
+) -> Result<Value<N>, EvalError> {
+    let function = context.function(identifier).cloned();
+    let arity = match (&function, Builtin::get(identifier)) {
+        (Some(function), _) => function.arity(),
+        (None, Some(builtin)) => builtin.arity,
+        (None, None) => return Err(EvalError::UnknownFunction(identifier.to_string(), span)),
+    };
+    if !arity.accepts(arguments.len()) {
+        return Err(EvalError::WrongArgumentCount {
+            function: identifier.to_string(),
+            expected: arity,
+            found: arguments.len(),
+            span,
+        });
+    }
+    let arguments = arguments
+        .iter()
+        .map(|argument| number(evaluate(argument, context)?, argument.span))
+        .collect::<Result<Vec<N>, EvalError>>()?;
+    let Some(function) = function else {
+        let result = Builtin::get(identifier)
+            .ok_or_else(|| EvalError::UnknownFunction(identifier.to_string(), span))?
+            .call(&arguments, span, context.angle_unit())?;
+        return check(Value::Number(result), span, context);
+    };
+    if !context.enter_call() {
+        return Err(EvalError::RecursionLimit {
+            function: identifier.to_string(),
+            limit: context.max_call_depth(),
+            span,
+        });
+    }
+    let result = evaluate(&function.apply(&arguments), context);
+    context.exit_call();
+    // Spans inside the body refer to the line defining the function, so point at the call.
+    result.map_err(|err| err.at(span))
+}
+
+/// Evaluates only the branch the condition selects.
+fn evaluate_conditional<N: Number>(
+    condition: &Expression<N>,
+    then: &Expression<N>,
+    otherwise: &Expression<N>,
+    context: &mut EvalContext<N>,
+) -> Result<Value<N>, EvalError> {
+    if boolean(evaluate(condition, context)?, condition.span)? {
+        evaluate(then, context)
+    } else {
+        evaluate(otherwise, context)
+    }
+}
+
+/// Evaluates the syntax tree in a single recursive walk. Each kind of node is handled by its own
+/// function, keeping the stack frames small for deeply nested expressions and calls.
+fn evaluate<N: Number>(
+    expression: &Expression<N>,
+    context: &mut EvalContext<N>,
+) -> Result<Value<N>, EvalError> {
+    if !context.enter() {
+        return Err(EvalError::DepthLimit(context.max_depth(), expression.span));
+    }
+    let result = match &expression.kind {
+        ExpressionKind::Literal(literal) => {
+            check(Value::Number(literal.clone()), expression.span, context)
+        }
+        ExpressionKind::Variable(identifier) => context
+            .get(identifier)
+            .map(Value::Number)
+            .ok_or_else(|| EvalError::UndefinedVariable(identifier.clone(), expression.span)),
+        ExpressionKind::Unary(operator, operand) => {
+            evaluate_unary(operator, operand, expression.span, context)
+        }
+        ExpressionKind::Binary(..) => evaluate_binary(expression, context),
+        ExpressionKind::Call(identifier, arguments) => {
+            evaluate_call(identifier, arguments, expression.span, context)
+        }
+        ExpressionKind::Conditional(condition, then, otherwise) => {
+            evaluate_conditional(condition, then, otherwise, context)
+        }
+        ExpressionKind::Invalid => Err(EvalError::ExpectedOperand(expression.span)),
+    };
+    context.exit();
+    result
+}
+
+impl<N: Number> Evaluate<N> for Expression<N> {
+    fn eval(&self, context: &mut EvalContext<N>) -> Result<Option<Value<N>>, EvalError> {
+        evaluate(self, context).map(Some)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum EvalError {
     ExpressionBuilderError(ExpressionBuilderError),
-    ExpectedOperand,
-    ExpectedOperator,
+    ExpectedOperand(Span),
+    ExpectedOperator(Span),
+    UndefinedVariable(String, Span),
+    UnknownFunction(String, Span),
+    /// A builtin only computed in floating point, such as `sin`, called with exact numbers.
+    FloatFunction(String, Span),
+    WrongArgumentCount {
+        function: String,
+        expected: Arity,
+        found: usize,
+        span: Span,
+    },
+    /// More nested calls than the context's limit, `MAX_CALL_DEPTH` by default, the span pointing
+    /// at the outermost call.
+    RecursionLimit {
+        function: String,
+        limit: usize,
+        span: Span,
+    },
+    /// Expressions nested deeper than the context's limit, `MAX_DEPTH` by default, counting those
+    /// in the functions called. The span points at the expression beyond the limit, or at the
+    /// call it was reached in.
+    DepthLimit(usize, Span),
+    /// The number type can't compute a result, the span pointing at the operator or call, or at
+    /// the operand of an integer operator that isn't an integer.
+    NumberError(NumberError, Span),
+    /// A boolean where arithmetic needs a number, as in `(1<2)+1`, the span pointing at the
+    /// operand. Variables and function arguments hold numbers too.
+    ExpectedNumber(Span),
+    /// A number where a condition is needed, as in `1 && x<2`, the span pointing at the operand.
+    ExpectedBoolean(Span),
+    /// The following are only reported with `FloatPolicy::Strict`, the span pointing at the
+    /// operator or call at fault.
+    DivisionByZero(Span),
+    /// A result too large to represent, which would otherwise be infinite.
+    Overflow(Span),
+    /// An undefined result such as `sqrt(-1)`, which would otherwise be NaN.
+    NotANumber(Span),
+}
+
+impl EvalError {
+    /// Location of the error in the evaluated source.
+    pub fn span(&self) -> Span {
+        match self {
+            EvalError::ExpressionBuilderError(err) => err.span(),
+            EvalError::ExpectedOperand(span)
+            | EvalError::ExpectedOperator(span)
+            | EvalError::DivisionByZero(span)
+            | EvalError::Overflow(span)
+            | EvalError::NotANumber(span)
+            | EvalError::UndefinedVariable(_, span)
+            | EvalError::UnknownFunction(_, span)
+            | EvalError::FloatFunction(_, span)
+            | EvalError::WrongArgumentCount { span, .. }
+            | EvalError::RecursionLimit { span, .. }
+            | EvalError::ExpectedNumber(span)
+            | EvalError::ExpectedBoolean(span)
+            | EvalError::NumberError(_, span)
+            | EvalError::DepthLimit(_, span) => *span,
+        }
+    }
+
+    /// Moves an error found while evaluating to `span`.
+    fn at(self, span: Span) -> Self {
+        match self {
+            EvalError::DivisionByZero(_) => EvalError::DivisionByZero(span),
+            EvalError::Overflow(_) => EvalError::Overflow(span),
+            EvalError::NotANumber(_) => EvalError::NotANumber(span),
+            EvalError::UndefinedVariable(identifier, _) => {
+                EvalError::UndefinedVariable(identifier, span)
+            }
+            EvalError::UnknownFunction(identifier, _) => EvalError::UnknownFunction(identifier, span),
+            EvalError::FloatFunction(identifier, _) => EvalError::FloatFunction(identifier, span),
+            EvalError::ExpectedNumber(_) => EvalError::ExpectedNumber(span),
+            EvalError::ExpectedBoolean(_) => EvalError::ExpectedBoolean(span),
+            EvalError::NumberError(err, _) => EvalError::NumberError(err, span),
+            EvalError::DepthLimit(limit, _) => EvalError::DepthLimit(limit, span),
+            EvalError::RecursionLimit { function, limit, .. } => EvalError::RecursionLimit {
+                function,
+                limit,
+                span,
+            },
+            EvalError::WrongArgumentCount {
+                function,
+                expected,
+                found,
+                ..
+            } => EvalError::WrongArgumentCount {
+                function,
+                expected,
+                found,
+                span,
+            },
+            err => err,
+        }
+    }
 }
 
 impl Display for EvalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             EvalError::ExpressionBuilderError(err) => write!(f, "{}", err),
-            EvalError::ExpectedOperand => write!(f, "Expected operand"),
-            EvalError::ExpectedOperator => write!(f, "Expected operator"),
+            EvalError::ExpectedOperand(_) => write!(f, "Expected operand"),
+            EvalError::ExpectedOperator(_) => write!(f, "Expected operator"),
+            EvalError::UndefinedVariable(identifier, _) => {
+                write!(f, "Undefined variable {}", identifier)
+            }
+            EvalError::UnknownFunction(identifier, _) => write!(f, "Unknown function {}", identifier),
+            EvalError::FloatFunction(identifier, _) => {
+                write!(f, "{} is only available with floating point numbers", identifier)
+            }
+            EvalError::WrongArgumentCount {
+                function,
+                expected,
+                found,
+                ..
+            } => write!(f, "{} expects {}, found {}", function, expected, found),
+            EvalError::RecursionLimit { function, limit, .. } => {
+                write!(f, "Recursion limit of {} calls reached in {}", limit, function)
+            }
+            EvalError::DepthLimit(limit, _) => {
+                write!(f, "Expression nested deeper than {} levels", limit)
+            }
+            EvalError::NumberError(err, _) => write!(f, "{}", err),
+            EvalError::ExpectedNumber(_) => write!(f, "Expected a number, found a boolean"),
+            EvalError::ExpectedBoolean(_) => write!(f, "Expected a boolean, found a number"),
+            EvalError::DivisionByZero(_) => write!(f, "Division by zero"),
+            EvalError::Overflow(_) => write!(f, "Result too large"),
+            EvalError::NotANumber(_) => write!(f, "Result is not a number"),
         }
     }
 }
@@ -92,16 +420,46 @@ impl Error for EvalError {}
 impl From<ExpressionBuilderError> for EvalError {
     fn from(err: ExpressionBuilderError) -> Self {
         match err {
-            ExpressionBuilderError::ExpectedOperator => EvalError::ExpectedOperator,
-            ExpressionBuilderError::ExpectedOperand => EvalError::ExpectedOperand,
+            ExpressionBuilderError::ExpectedOperator(span) => EvalError::ExpectedOperator(span),
+            ExpressionBuilderError::ExpectedOperand(span) => EvalError::ExpectedOperand(span),
             err => EvalError::ExpressionBuilderError(err),
         }
     }
 }
 
-impl Evaluate for &str {
-    fn eval(&self) -> Result<Option<f32>, EvalError> {
-        ExpressionItem::from(ExpressionBuilder::new(self).get_expression()?).eval()
+impl<N: Number> Evaluate<N> for Statement<N> {
+    fn eval(&self, context: &mut EvalContext<N>) -> Result<Option<Value<N>>, EvalError> {
+        match &self.kind {
+            StatementKind::Expression(expression) => expression.eval(context),
+            StatementKind::Assignment(identifier, expression) => {
+                let value = number(evaluate(expression, context)?, expression.span)?;
+                context.set(identifier, value.clone());
+                Ok(Some(Value::Number(value)))
+            }
+            StatementKind::Definition(identifier, parameters, expression) => {
+                context.define(
+                    identifier,
+                    UserFunction::new(parameters.clone(), expression.clone()),
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<N: Number> Evaluate<N> for &str {
+    fn eval(&self, context: &mut EvalContext<N>) -> Result<Option<Value<N>>, EvalError> {
+        // Statements separated by `;` are evaluated in order, the last one giving the result.
+        match ExpressionBuilder::new(self).with_context(context).get_statements() {
+            Ok(statements) => statements
+                .iter()
+                .try_fold(None, |_, statement| statement.eval(context)),
+            // The builder only fails with at least one error, reported in order.
+            Err(errors) => Err(errors
+                .into_iter()
+                .next()
+                .map_or(EvalError::ExpectedOperand(Span::new(0, self.len())), EvalError::from)),
+        }
     }
 }
 
@@ -109,42 +467,580 @@ impl Evaluate for &str {
 #[cfg(test)]
 mod eval_test {
     use super::*;
-    use crate::expression_builder::ExpressionBuilder;
-
-
+    use bigdecimal::BigDecimal;
+    use crate::angle::AngleUnit;
+    use crate::context::{MAX_CALL_DEPTH, MAX_DEPTH};
+    use crate::expression_builder::{ExpressionBuilder, MAX_NESTING};
+    use crate::rational::Rational;
+    use bigdecimal::num_bigint::BigInt;
+    use proptest::prelude::*;
 
     #[test]
     fn eval_str() {
         let expression = "1+1";
-        let expression_items = ExpressionItem::from(ExpressionBuilder::new("1+1").get_expression().unwrap());
-        let expression_eval = expression_items.eval();
+        let expression_tree = ExpressionBuilder::new("1+1").get_expression().unwrap();
+        let expression_eval = expression_tree.eval(&mut EvalContext::<f64>::new());
         assert!(expression_eval.is_ok());
         assert!(expression_eval.as_ref().unwrap().is_some());
-        assert_eq!(expression_eval.as_ref().unwrap().unwrap(), 2.0);
-        assert_eq!(expression.eval(), expression_eval);
+        assert_eq!(expression_eval.as_ref().unwrap().as_ref().unwrap(), &Value::Number(2.0));
+        assert_eq!(expression.eval(&mut EvalContext::<f64>::new()), expression_eval);
     }
 
     #[test]
     fn eval_ok() {
-        assert!(r"-1".eval().is_ok_and(|ok| ok.is_some_and(|some| some == -1.0)));
-        assert!(r"1+1".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
-        assert!(r"(1)+0".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
-        assert!(r"1.5+(1)".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 2.5)));
-        assert!(r"1".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
-        assert!(r"-100.0".eval().is_ok_and(|ok| ok.is_some_and(|some| some == -100.0)));
-        assert!(r"1+1+1+1".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 4.0)));
-        assert!(r"5+(10*2)".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 25.0)));
-        assert!(r"1+-1+1+1".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
-        assert!(r"2+2-2*2/2%2^2".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
-        assert!(r"1+(1)".eval().is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"-1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == -1.0)));
+        assert!(r"1+1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"(1)+0".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"1.5+(1)".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 2.5)));
+        assert!(r"1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"-100.0".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == -100.0)));
+        assert!(r"1+1+1+1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 4.0)));
+        assert!(r"5+(10*2)".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 25.0)));
+        assert!(r"1+-1+1+1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"2+2-2*2/2%2^2".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"1+(1)".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"-(1+2)".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == -3.0)));
+        assert!(r"+(1)".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"--1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"1--1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"2*-(1+1)".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == -4.0)));
+        assert!(r"-sqrt(4)+abs(-(1-3))".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 0.0)));
+        assert!(r"-pi".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == -std::f64::consts::PI)));
+    }
+
+    #[test]
+    fn eval_precedence() {
+        for (expression, result) in [
+            ("2^3^2", 512.0),
+            ("2^3^0", 2.0),
+            ("(2^3)^2", 64.0),
+            ("-2^2", -4.0),
+            ("(-2)^2", 4.0),
+            ("2^-2", 0.25),
+            ("-2^-2", -0.25),
+            ("-2*3", -6.0),
+            ("2*3^2", 18.0),
+            ("2^3*2", 16.0),
+            ("1-2-3", -4.0),
+            ("8/4/2", 1.0),
+            ("7%4%2", 1.0),
+            ("1+-2^2", -3.0),
+            ("7//2*2", 6.0),
+            ("1|6&3", 3.0),
+            ("1|2 xor 3", 1.0),
+            ("1<<2+1", 8.0),
+            ("6&3<<1", 6.0),
+            ("~1+1", -1.0),
+            ("-3!", -6.0),
+            ("2^3!", 64.0),
+            ("3!^2", 36.0),
+        ] {
+            assert!(expression.eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok == Some(Value::Number(result))), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn eval_conditions() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"x = 2".eval(&mut context).is_ok());
+        for (expression, result) in [
+            ("x < 3", true),
+            ("x <= 2", true),
+            ("x > 2", false),
+            ("x >= 2.5", false),
+            ("x == 2", true),
+            ("x != 2", false),
+            ("1 + 1 == x", true),
+            ("x > 1 && x < 3", true),
+            ("x < 1 || x > 3", false),
+            ("!(x < 1) && !(x > 3)", true),
+            ("!x < 1", true),
+            ("(x < 1) == (x > 3)", true),
+            ("(x < 1) != (x < 3)", true),
+            ("x > 1 || 1/0 > 1", true),
+        ] {
+            assert!(expression.eval(&mut context).is_ok_and(|ok| ok == Some(Value::Boolean(result))), "{}", expression);
+        }
+        assert!(r"positive(y) = y > 0".eval(&mut context).is_ok());
+        assert!(r"positive(-x) || positive(x)".eval(&mut context).is_ok_and(|ok| ok == Some(Value::Boolean(true))));
+        assert!(r"(x < 3) + 1".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedNumber(Span::new(0, 7))));
+        assert!(r"(x < 3) < (x < 4)".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedNumber(Span::new(0, 7))));
+        assert!(r"(x < 3) == 1".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedNumber(Span::new(0, 7))));
+        assert!(r"y = x < 3".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedNumber(Span::new(4, 9))));
+        assert!(r"sqrt(x < 3)".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedNumber(Span::new(5, 10))));
+        assert!(r"x && x < 3".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedBoolean(Span::new(0, 1))));
+        assert!(r"x < 3 && x".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedBoolean(Span::new(9, 10))));
+        assert!(r"!x".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedBoolean(Span::new(1, 2))));
+        assert!(r"y".eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("y".to_string(), Span::new(0, 1))));
+    }
+
+    #[test]
+    fn eval_conditional() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"tax(income) = income <= 10000 ? 0 : income <= 40000 ? (income - 10000) * 0.2 : 6000 + (income - 40000) * 0.4".eval(&mut context).is_ok());
+        for (income, tax) in [(5000.0, 0.0), (20000.0, 2000.0), (50000.0, 10000.0)] {
+            assert!(format!("tax({})", income).as_str().eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == tax)));
+        }
+        assert!(r"fact(n) = n <= 1 ? 1 : n * fact(n - 1)".eval(&mut context).is_ok());
+        assert!(r"fact(5)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 120.0)));
+        assert!(r"1 < 2 ? 1 < 3 : 4".eval(&mut context).is_ok_and(|ok| ok == Some(Value::Boolean(true))));
+        assert!(r"1 ? 2 : 3".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedBoolean(Span::new(0, 1))));
+        assert!(r"1 < 2 ? 1 : undefined".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"1 > 2 ? 1 : undefined".eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("undefined".to_string(), Span::new(12, 21))));
+        let mut context = EvalContext::<BigDecimal>::new();
+        assert!(r"x = 0; x != 0 ? 1/x : 0".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == BigDecimal::from(0))));
+        assert!(r"x == 0 ? 1/x : 0".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::DivisionByZero, Span::new(10, 11))));
+    }
+
+    #[test]
+    fn eval_integer_operators() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"x = 12; x & 10 | 1".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 9.0)));
+        assert!(r"x xor 5".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 9.0)));
+        assert!(r"x >> 2 << 1".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 6.0)));
+        assert!(r"-x // 5".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == -3.0)));
+        assert!(r"(x - 7)!".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 120.0)));
+        assert!(r"1.5 & 1".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::NotAnInteger, Span::new(0, 3))));
+        assert!(r"4 >> 0.5".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::NotAnInteger, Span::new(5, 8))));
+        assert!(r"~0.5".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::NotAnInteger, Span::new(1, 4))));
+        assert!(r"(1/2)!".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::NotAnInteger, Span::new(0, 5))));
+        assert!(r"1 << -1".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::OutOfRange, Span::new(2, 4))));
+        let mut context = EvalContext::<BigDecimal>::new();
+        assert!(r"25! >> 64".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == "840864".parse::<BigDecimal>().unwrap())));
+    }
+
+    #[test]
+    fn eval_strict() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"1/0".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == f64::INFINITY)));
+        assert!(r"0/0".eval(&mut context).is_ok_and(|ok| ok.and_then(Value::number).is_some_and(f64::is_nan)));
+        context.set_policy(FloatPolicy::Strict);
+        assert!(r"1/0".eval(&mut context).is_err_and(|err| err == EvalError::DivisionByZero(Span::new(1, 2))));
+        assert!(r"0 % 0".eval(&mut context).is_err_and(|err| err == EvalError::DivisionByZero(Span::new(2, 3))));
+        assert!(r"1 // 0".eval(&mut context).is_err_and(|err| err == EvalError::DivisionByZero(Span::new(2, 4))));
+        assert!(r"1e308*10".eval(&mut context).is_err_and(|err| err == EvalError::Overflow(Span::new(5, 6))));
+        assert!(r"171!".eval(&mut context).is_err_and(|err| err == EvalError::Overflow(Span::new(3, 4))));
+        assert!(r"1e999".eval(&mut context).is_err_and(|err| err == EvalError::Overflow(Span::new(0, 5))));
+        assert!(r"2 + sqrt(-1)".eval(&mut context).is_err_and(|err| err == EvalError::NotANumber(Span::new(4, 12))));
+        assert!(r"f(x) = 1/x".eval(&mut context).is_ok());
+        assert!(r"1 + f(0)".eval(&mut context).is_err_and(|err| err == EvalError::DivisionByZero(Span::new(4, 8))));
+        assert!(r"x = 0; x != 0 ? 1/x : 0".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 0.0)));
+        assert!(r"1/3".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0 / 3.0)));
+        let mut context = EvalContext::<Rational>::new();
+        context.set_policy(FloatPolicy::Strict);
+        assert!(r"0^-1".eval(&mut context).is_err_and(|err| err == EvalError::DivisionByZero(Span::new(1, 2))));
+    }
+
+    #[test]
+    fn eval_implicit_multiplication() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"x=3".eval(&mut context).is_ok());
+        assert!(r"2(3+4)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 14.0)));
+        assert!(r"(1+2)(3+4)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 21.0)));
+        assert!(r"3x".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 9.0)));
+        assert!(r"2x^2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 18.0)));
+        assert!(r"1/2x".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.5)));
+        assert!(r"2pi".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0 * std::f64::consts::PI)));
+        assert!(r"f(x)=2x+1".eval(&mut context).is_ok());
+        assert!(r"3f(2)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 15.0)));
+        assert!(r"(1+2)3".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 9.0)));
+        assert!(r"f(1)2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 6.0)));
+        assert!(r"2 3".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedOperator(Span::new(2, 3))));
+        assert!(r"x(2)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 6.0)));
+        for (variable, literal) in [("x(2)^2", "3(2)^2"), ("1/x(2)", "1/3(2)"), ("x(2)!", "3(2)!")] {
+            let literal = literal.eval(&mut context).unwrap();
+            assert!(variable.eval(&mut context).is_ok_and(|ok| ok == literal), "{}", variable);
+        }
+        assert!(r"x(2)^2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 12.0)));
+        assert!(r"sin = 1; sin(0)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 0.0)));
+        assert!(r"g(y)=y(x)+1".eval(&mut context).is_ok());
+        assert!(r"g(4)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 13.0)));
+        assert!(r"x(1, 2)".eval(&mut context).is_err());
+        assert!(r"z = 2; z(3)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 6.0)));
+        assert!(r"y(2)".eval(&mut context).is_err_and(|err| err == EvalError::UnknownFunction("y".to_string(), Span::new(0, 4))));
+        assert!(r"2y".eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("y".to_string(), Span::new(1, 2))));
+    }
+
+    #[test]
+    fn eval_long_chain() {
+        let mut context = EvalContext::<f64>::new();
+        let sum = format!("1{}", "+1".repeat(999));
+        assert!(sum.as_str().eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1000.0)));
+        let mixed = format!("0{}", "+2*3-1".repeat(999));
+        assert!(mixed.as_str().eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 4995.0)));
+        assert!(context.is_unwound());
+    }
+
+    #[test]
+    fn eval_whitespace() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"1 + 2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 3.0)));
+        assert!(" \t1\n+\t2 * 3\n".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 7.0)));
+        assert!(r" x = 2 ^ 3 ".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 8.0)));
+        assert!(r"f (y) = y - x".eval(&mut context).is_ok_and(|ok| ok.is_none()));
+        assert!(r" - f ( 10 ) ".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == -2.0)));
+        assert!(r"1 + ".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(4, 5))));
+    }
+
+    #[test]
+    fn eval_statements() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"a = 2; b = a*3; a+b".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 8.0)));
+        assert!(r"g(x) = x*b;".eval(&mut context).is_ok_and(|ok| ok.is_none()));
+        assert!(r"g(2)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 12.0)));
+        assert!(r"(1+)*(2*)".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(3, 4))));
+        assert!(r"c = 1; undefined; c = 3".eval(&mut context).is_err());
+        assert!(r"c".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
     }
 
     #[test]
     fn eval_err() {
-        assert!(r"+".eval().is_err_and(|err| err == EvalError::ExpectedOperand));
-        assert!(r"".eval().is_err_and(|err| err == EvalError::ExpectedOperand));
-        assert!(r"(1".eval().is_err_and(|err| err == ExpressionBuilderError::ExpectedClosingParentheses.into()));
-        assert!(r"1)".eval().is_err_and(|err| err == EvalError::ExpectedOperator));
-        assert!(r"1+".eval().is_err_and(|err| err == EvalError::ExpectedOperand));
+        assert!(r"+".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(1, 2))));
+        assert!(r"*".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(0, 1))));
+        assert!(r"-()".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(2, 3))));
+        assert!(r"1*-".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(3, 4))));
+        assert!(r"-y".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::UndefinedVariable("y".to_string(), Span::new(1, 2))));
+        assert!(r"".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(0, 1))));
+        assert!(r"(1".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == ExpressionBuilderError::ExpectedClosingParentheses(Span::new(0, 2)).into()));
+        assert!(r"1)".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperator(Span::new(1, 2))));
+        assert!(r"1+".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(2, 3))));
+        assert!(r"1+()".eval(&mut EvalContext::<f64>::new()).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(3, 4))));
+    }
+
+    #[test]
+    fn eval_tree() {
+        let expression = Expression::binary(
+            Expression::new(ExpressionKind::Literal(2.0), Span::new(0, 1)),
+            Operator::Multiply,
+            Span::new(1, 2),
+            Expression::unary(
+                UnaryOperator::Negate,
+                Expression::new(ExpressionKind::Variable("x".to_string()), Span::new(3, 4)),
+                Span::new(2, 4),
+            ),
+        );
+        let mut context = EvalContext::<f64>::new();
+        assert!(expression.eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("x".to_string(), Span::new(3, 4))));
+        assert_eq!(expression.eval(&mut context).unwrap_err().span(), Span::new(3, 4));
+        context.set("x", 3.0);
+        assert!(expression.eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == -6.0)));
+    }
+
+    #[test]
+    fn eval_variables() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"x".eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("x".to_string(), Span::new(0, 1))));
+        assert!(r"x = 3*4".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 12.0)));
+        assert!(r"x".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 12.0)));
+        assert!(r"y=x/(x-8)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 3.0)));
+        assert!(r"x=x+y".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 15.0)));
+        assert!(r"2*x_1".eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("x_1".to_string(), Span::new(2, 5))));
+        assert!(r"z=".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(2, 3))));
+        assert!(r"-x".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == -15.0)));
+        assert_eq!(context.get("x"), Some(15.0));
+        assert_eq!(context.get("z"), None);
+    }
+
+    #[test]
+    fn eval_functions() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"sqrt(16)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 4.0)));
+        assert!(r"1+abs(-2)*2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 5.0)));
+        assert!(r"max(1, 5, 3)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 5.0)));
+        assert!(r"min(4-1,(2))".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"log(8,2)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 3.0)));
+        assert!(r"x=sqrt(sqrt(16))".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"cos(0)*x".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"round(pi*100)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 314.0)));
+        assert!(r"ln(1)+exp(0)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+    }
+
+    #[test]
+    fn eval_functions_err() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"foo(1)".eval(&mut context).is_err_and(|err| err == EvalError::UnknownFunction("foo".to_string(), Span::new(0, 6))));
+        assert!(r"sqrt(1,2)".eval(&mut context).is_err_and(|err| err == EvalError::WrongArgumentCount {
+            function: "sqrt".to_string(),
+            expected: Arity::exact(1),
+            found: 2,
+            span: Span::new(0, 9),
+        }));
+        assert!(r"max()".eval(&mut context).is_err_and(|err| err == EvalError::WrongArgumentCount {
+            function: "max".to_string(),
+            expected: Arity::at_least(1),
+            found: 0,
+            span: Span::new(0, 5),
+        }));
+        assert!(r"sqrt(1,)".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(7, 8))));
+        assert!(r"sqrt(1".eval(&mut context).is_err_and(|err| err == ExpressionBuilderError::ExpectedClosingParentheses(Span::new(0, 6)).into()));
+    }
+
+    #[test]
+    fn eval_user_functions() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"f(x, y) = x^2+y".eval(&mut context).is_ok_and(|ok| ok.is_none()));
+        assert!(r"f(3,1)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 10.0)));
+        assert!(r"f(-2,0)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 4.0)));
+        assert!(r"two()=2".eval(&mut context).is_ok_and(|ok| ok.is_none()));
+        assert!(r"g(x)=f(x,two())*y".eval(&mut context).is_ok_and(|ok| ok.is_none()));
+        assert!(r"g(1)".eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("y".to_string(), Span::new(0, 4))));
+        assert!(r"y=2".eval(&mut context).is_ok());
+        assert!(r"g(1)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 6.0)));
+        assert!(r"sqrt(x)=x".eval(&mut context).is_ok());
+        assert!(r"sqrt(3)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 3.0)));
+        assert_eq!(context.get("x"), None);
+    }
+
+    #[test]
+    fn eval_user_functions_err() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"f(x)=f(x)+1".eval(&mut context).is_ok());
+        assert!(r"f(1)".eval(&mut context).is_err_and(|err| err == EvalError::RecursionLimit {
+            function: "f".to_string(),
+            limit: MAX_CALL_DEPTH,
+            span: Span::new(0, 4),
+        }));
+        assert!(r"1+f(1)".eval(&mut context).is_err_and(|err| err.span() == Span::new(2, 6)));
+        assert!(r"1+1".eval(&mut context).is_ok());
+        assert!(r"g(x)=x".eval(&mut context).is_ok());
+        assert!(r"h(x) = g(x, x)".eval(&mut context).is_ok());
+        assert!(r"2*h(1)".eval(&mut context).is_err_and(|err| err.span() == Span::new(2, 6)));
+        assert!(r"g(1,2)".eval(&mut context).is_err_and(|err| err == EvalError::WrongArgumentCount {
+            function: "g".to_string(),
+            expected: Arity::exact(1),
+            found: 2,
+            span: Span::new(0, 6),
+        }));
+        assert!(r"f(=1".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedOperand(Span::new(2, 3))));
+    }
+
+    #[test]
+    fn eval_context_options() {
+        let mut context = EvalContext::<f64>::new();
+        context.set_angle_unit(AngleUnit::Degrees);
+        assert!(r"sin(90)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"atan2(1, 0)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 90.0)));
+        context.set_angle_unit(AngleUnit::Gradians);
+        assert!(r"acos(-1)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 200.0)));
+        context.set_max_call_depth(3);
+        assert!(r"f(x) = x < 1 ? 0 : f(x-1)".eval(&mut context).is_ok());
+        assert!(r"f(2)".eval(&mut context).is_ok());
+        assert!(r"f(3)".eval(&mut context).is_err_and(|err| err.to_string() == "Recursion limit of 3 calls reached in f"));
+        context.set_max_depth(2);
+        assert!(r"-1".eval(&mut context).is_ok());
+        assert!(r"--1".eval(&mut context).is_err_and(|err| err == EvalError::DepthLimit(2, Span::new(2, 3))));
+    }
+
+    #[test]
+    fn eval_angles() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"sin(90deg)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"sin(pi/2)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"cos(200grad)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == -1.0)));
+        context.set_angle_unit(AngleUnit::Degrees);
+        assert!(r"sin(90)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"sin((pi/2)rad)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 1.0)));
+        assert!(r"100grad + 90deg".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 180.0)));
+        assert!(r"asin(1) == 90deg".eval(&mut context).is_ok_and(|ok| ok == Some(Value::Boolean(true))));
+        context.set_policy(FloatPolicy::Strict);
+        assert!(r"1 + 1e308rad".eval(&mut context).is_err_and(|err| err == EvalError::Overflow(Span::new(9, 12))));
+        let mut context = EvalContext::<BigDecimal>::new();
+        assert!(r"90rad".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == BigDecimal::from(90))));
+        assert!(r"90deg".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::Inexact, Span::new(2, 5))));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn eval_number_types() {
+        assert!(r"16777217+1".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 16777218.0)));
+        assert!(r"16777217+1".eval(&mut EvalContext::<f32>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 16777216.0)));
+        assert!(r"0.1+0.2".eval(&mut EvalContext::<f64>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 0.1 + 0.2)));
+        assert!(r"sqrt(2)".eval(&mut EvalContext::<f32>::new()).is_ok_and(|ok| ok.is_some_and(|some| some == 2f32.sqrt())));
+    }
+
+    #[test]
+    fn eval_decimal() {
+        let mut context = EvalContext::<BigDecimal>::new();
+        let decimal = |number: &str| number.parse::<BigDecimal>().unwrap();
+        assert!(r"0.1+0.2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == decimal("0.3"))));
+        assert!(r"2^64+1".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == decimal("18446744073709551617"))));
+        assert!(r"x=1/8".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == decimal("0.125"))));
+        assert!(r"round(x*100)/100".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == decimal("0.13"))));
+        assert!(r"1/3".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::Inexact, Span::new(1, 2))));
+        assert!(r"2^0.5".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::Inexact, Span::new(1, 2))));
+        assert!(r"sqrt(4)+cbrt(0.008)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == decimal("2.2"))));
+        assert!(r"sqrt(2)".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::Inexact, Span::new(0, 7))));
+        assert!(r"1+sin(0)".eval(&mut context).is_err_and(|err| err == EvalError::FloatFunction("sin".to_string(), Span::new(2, 8))));
+        assert!(r"1%0".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::DivisionByZero, Span::new(1, 2))));
+        assert!(r"pi".eval(&mut context).is_err_and(|err| err == EvalError::UndefinedVariable("pi".to_string(), Span::new(0, 2))));
+    }
+
+    #[test]
+    fn eval_rational() {
+        let mut context = EvalContext::<Rational>::new();
+        let rational = |numerator: i64, denominator: i64| Rational::new(numerator.into(), denominator.into()).unwrap();
+        assert!(r"1/3+1/6".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == rational(1, 2))));
+        assert!(r"0.1+0.2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == rational(3, 10))));
+        assert!(r"f(x)=x/3".eval(&mut context).is_ok_and(|ok| ok.is_none()));
+        assert!(r"f(1)*3".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == rational(1, 1))));
+        assert!(r"(2/3)^-2".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == rational(9, 4))));
+        assert!(r"2^0.5".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::Inexact, Span::new(1, 2))));
+        assert!(r"sqrt(4/9)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == rational(2, 3))));
+        assert!(r"cos(0)".eval(&mut context).is_err_and(|err| err == EvalError::FloatFunction("cos".to_string(), Span::new(0, 6))));
+        assert!(r"1/(1-1)".eval(&mut context).is_err_and(|err| err == EvalError::NumberError(NumberError::DivisionByZero, Span::new(1, 2))));
+    }
+
+    const OPERATORS: [Operator; 20] = [
+        Operator::Add, Operator::Subtract, Operator::Multiply, Operator::Divide, Operator::Power,
+        Operator::Remainder, Operator::FloorDivide, Operator::BitAnd, Operator::BitOr,
+        Operator::BitXor, Operator::ShiftLeft, Operator::ShiftRight, Operator::Less,
+        Operator::LessEqual, Operator::Equal, Operator::NotEqual, Operator::Greater,
+        Operator::GreaterEqual, Operator::And, Operator::Or,
+    ];
+    const UNARY_OPERATORS: [UnaryOperator; 7] = [
+        UnaryOperator::Plus, UnaryOperator::Negate, UnaryOperator::BitNot,
+        UnaryOperator::Factorial, UnaryOperator::Not, UnaryOperator::Angle(AngleUnit::Degrees),
+        UnaryOperator::Angle(AngleUnit::Gradians),
+    ];
+    const IDENTIFIERS: [&str; 8] = ["x", "pi", "undefined", "f", "g", "sqrt", "max", "log"];
+
+    const TOKENS: [&str; 25] = [
+        "0", "1", "9", "0.5", "1e999", "0x1F", "x", "f", "g", "sqrt", "deg", "+", "-", "*", "/",
+        "^", "//", "<<", "!", "~", "?", ":", "(", ")", ",",
+    ];
+
+    /// Random characters rarely make a valid expression, so sources are also built from tokens.
+    fn source() -> impl Strategy<Value = String> {
+        prop_oneof![
+            r"[0-9a-z_.,;:?!~^*/%+\-&|<>=() é]{0,32}",
+            prop::collection::vec(prop::sample::select(&TOKENS[..]), 0..16)
+                .prop_map(|tokens| tokens.concat()),
+        ]
+    }
+
+    fn span() -> impl Strategy<Value = Span> {
+        let offset = || prop_oneof![0..16usize, Just(usize::MAX), any::<usize>()];
+        (offset(), offset()).prop_map(|(start, end)| Span::new(start, end))
+    }
+
+    fn identifier() -> impl Strategy<Value = String> {
+        prop::sample::select(&IDENTIFIERS[..]).prop_map(str::to_string)
+    }
+
+    /// Any tree the public constructors can build, whether or not the builder would produce it.
+    fn tree<N: Number + 'static>() -> impl Strategy<Value = Expression<N>> {
+        let literal = prop_oneof![
+            -1000.0..1000.0,
+            (-20i32..20).prop_map(f64::from),
+            Just(f64::NAN),
+            Just(f64::INFINITY),
+            Just(1e300),
+        ]
+        // Exact types can't hold NaN or infinity, nor some fractions, so those are truncated.
+        .prop_map(|number| {
+            N::from_f64(number)
+                .or_else(|_| N::from_integer(BigInt::from(number as i64)))
+                .unwrap()
+        });
+        let leaf = prop_oneof![
+            literal.prop_map(ExpressionKind::Literal),
+            identifier().prop_map(ExpressionKind::Variable),
+            identifier().prop_map(|identifier| ExpressionKind::Call(identifier, vec![])),
+            Just(ExpressionKind::Invalid),
+        ];
+        (leaf, span())
+            .prop_map(|(kind, span)| Expression::new(kind, span))
+            .prop_recursive(6, 64, 3, |inner| {
+                let kind = prop_oneof![
+                    (prop::sample::select(&UNARY_OPERATORS[..]), inner.clone())
+                        .prop_map(|(operator, operand)| ExpressionKind::Unary(operator, Box::new(operand))),
+                    (inner.clone(), prop::sample::select(&OPERATORS[..]), span(), inner.clone())
+                        .prop_map(|(left, operator, span, right)| {
+                            ExpressionKind::Binary(Box::new(left), operator, span, Box::new(right))
+                        }),
+                    (identifier(), prop::collection::vec(inner.clone(), 0..4))
+                        .prop_map(|(identifier, arguments)| ExpressionKind::Call(identifier, arguments)),
+                    (inner.clone(), inner.clone(), inner).prop_map(|(condition, then, otherwise)| {
+                        ExpressionKind::Conditional(Box::new(condition), Box::new(then), Box::new(otherwise))
+                    }),
+                ];
+                (kind, span()).prop_map(|(kind, span)| Expression::new(kind, span))
+            })
+    }
+
+    /// A fresh context in either policy, with a recursive and a two parameter function.
+    fn sample_context<N: Number>(strict: bool) -> EvalContext<N> {
+        let mut context = EvalContext::new();
+        if strict {
+            context.set_policy(FloatPolicy::Strict);
+        }
+        assert!(r"x = 3; f(x) = x < 1 ? 1 : x*f(x-1); g(x, y) = x/y".eval(&mut context).is_ok());
+        context
+    }
+
+    fn eval_total<N: Number + 'static>(source: &str, tree: Expression<N>, strict: bool) {
+        let mut context = sample_context::<N>(strict);
+        let _ = source.eval(&mut context);
+        let _ = Statement::new(StatementKind::Expression(tree.clone())).eval(&mut context);
+        let _ = Statement::new(StatementKind::Assignment("x".to_string(), tree.clone()))
+            .eval(&mut context);
+        let _ = Statement::new(StatementKind::Definition("h".to_string(), vec!["x".to_string()], tree))
+            .eval(&mut context);
+        let _ = "h(2) + h(x)".eval(&mut context);
+        assert!(context.is_unwound());
+    }
+
+    #[test]
+    fn eval_depth_restored() {
+        let mut context = EvalContext::<f64>::new();
+        assert!(r"f(x) = x ? 1 : 2".eval(&mut context).is_ok());
+        for _ in 0..=MAX_DEPTH {
+            assert!(r"1 ? 2 : 3".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedBoolean(Span::new(0, 1))));
+            assert!(r"f(1)".eval(&mut context).is_err_and(|err| err == EvalError::ExpectedBoolean(Span::new(0, 4))));
+        }
+        assert!(r"1+1".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+    }
+
+    #[test]
+    fn eval_depth_limit() {
+        let mut context = EvalContext::<f64>::new();
+        let literal = || Expression::new(ExpressionKind::Literal(1.0), Span::new(0, 1));
+        let mut expression = literal();
+        for _ in 0..MAX_DEPTH {
+            expression = Expression::binary(literal(), Operator::Add, Span::new(1, 2), expression);
+        }
+        assert!(expression.eval(&mut context).is_err_and(|err| err == EvalError::DepthLimit(MAX_DEPTH, Span::new(0, 1))));
+        assert!(r"1+1".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 2.0)));
+        assert!(r"f(x) = x < 1 ? 0 : 1+(1+(1+(1+f(x-1))))".eval(&mut context).is_ok());
+        assert!(r"f(300)".eval(&mut context).is_err_and(|err| err == EvalError::DepthLimit(MAX_DEPTH, Span::new(0, 6))));
+        assert!(r"f(30)".eval(&mut context).is_ok_and(|ok| ok.is_some_and(|some| some == 120.0)));
+    }
+
+    /// The default limits keep evaluation within a spawned thread's default stack.
+    #[test]
+    fn eval_depth_limit_stack() {
+        std::thread::spawn(|| {
+            let mut context = EvalContext::<f64>::new();
+            assert!(r"f(x) = x < 1 ? 0 : 1+(1+(1+(1+f(x-1)))); f(300)".eval(&mut context).is_err());
+            assert!(r"g(x) = x < 1 ? 0 : -g(x-1); g(1000)".eval(&mut context).is_err());
+            let source = format!("0{}1", "-".repeat(MAX_NESTING - 2));
+            assert!(source.as_str().eval(&mut context).is_ok());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn eval_never_panics() {
+        proptest!(|(
+            source in source(),
+            float in tree::<f64>(),
+            decimal in tree::<BigDecimal>(),
+            rational in tree::<Rational>(),
+            strict in any::<bool>(),
+        )| {
+            eval_total(&source, float, strict);
+            eval_total(&source, decimal, strict);
+            eval_total(&source, rational, strict);
+        });
+    }
+}
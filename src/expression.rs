@@ -0,0 +1,243 @@
+use std::fmt::Display;
+
+use crate::{
+    number::Number,
+    operator::{Operator, UnaryOperator},
+    span::Span,
+};
+
+/// Node of the syntax tree built by `ExpressionBuilder`, `span` covers the source it was read from.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Expression<N = f64> {
+    pub kind: ExpressionKind<N>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExpressionKind<N = f64> {
+    Literal(N),
+    Variable(String),
+    Unary(UnaryOperator, Box<Expression<N>>),
+    /// The span between the operands is the operator's own, empty for an implicit multiplication.
+    Binary(Box<Expression<N>>, Operator, Span, Box<Expression<N>>),
+    Call(String, Vec<Expression<N>>),
+    /// `condition ? then : otherwise`, only the selected branch is evaluated.
+    Conditional(Box<Expression<N>>, Box<Expression<N>>, Box<Expression<N>>),
+    /// Stands in for source that failed to parse, so the builder can keep looking for errors past
+    /// it. Never part of a successfully built tree.
+    Invalid,
+}
+
+impl<N> Expression<N> {
+    pub fn new(kind: ExpressionKind<N>, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn unary(operator: UnaryOperator, operand: Expression<N>, span: Span) -> Self {
+        Self::new(ExpressionKind::Unary(operator, Box::new(operand)), span)
+    }
+
+    /// Joins two operands, spanning from the start of `left` to the end of `right`.
+    pub fn binary(
+        left: Expression<N>,
+        operator: Operator,
+        operator_span: Span,
+        right: Expression<N>,
+    ) -> Self {
+        let span = Span::new(left.span.start, right.span.end);
+        Self::new(
+            ExpressionKind::Binary(Box::new(left), operator, operator_span, Box::new(right)),
+            span,
+        )
+    }
+
+    /// Spans from the start of `condition` to the end of `otherwise`.
+    pub fn conditional(
+        condition: Expression<N>,
+        then: Expression<N>,
+        otherwise: Expression<N>,
+    ) -> Self {
+        let span = Span::new(condition.span.start, otherwise.span.end);
+        Self::new(
+            ExpressionKind::Conditional(Box::new(condition), Box::new(then), Box::new(otherwise)),
+            span,
+        )
+    }
+}
+
+impl<N: Number> Expression<N> {
+    /// Replaces every variable named in `parameters` with the matching value from `arguments`.
+    pub fn substitute(&self, parameters: &[String], arguments: &[N]) -> Expression<N> {
+        let kind = match &self.kind {
+            ExpressionKind::Variable(identifier) => parameters
+                .iter()
+                .zip(arguments)
+                .find(|(parameter, _)| *parameter == identifier)
+                .map_or_else(
+                    || self.kind.clone(),
+                    |(_, argument)| ExpressionKind::Literal(argument.clone()),
+                ),
+            ExpressionKind::Unary(operator, operand) => ExpressionKind::Unary(
+                operator.clone(),
+                Box::new(operand.substitute(parameters, arguments)),
+            ),
+            ExpressionKind::Binary(left, operator, operator_span, right) => ExpressionKind::Binary(
+                Box::new(left.substitute(parameters, arguments)),
+                operator.clone(),
+                *operator_span,
+                Box::new(right.substitute(parameters, arguments)),
+            ),
+            ExpressionKind::Call(identifier, call_arguments) => ExpressionKind::Call(
+                identifier.clone(),
+                call_arguments
+                    .iter()
+                    .map(|argument| argument.substitute(parameters, arguments))
+                    .collect(),
+            ),
+            ExpressionKind::Conditional(condition, then, otherwise) => ExpressionKind::Conditional(
+                Box::new(condition.substitute(parameters, arguments)),
+                Box::new(then.substitute(parameters, arguments)),
+                Box::new(otherwise.substitute(parameters, arguments)),
+            ),
+            ExpressionKind::Literal(_) | ExpressionKind::Invalid => self.kind.clone(),
+        };
+        Expression::new(kind, self.span)
+    }
+}
+
+/// Writes every binary operation in parentheses, so the output shows how the source was grouped.
+impl<N: Display> Display for Expression<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ExpressionKind::Literal(literal) => write!(f, "{}", literal),
+            ExpressionKind::Variable(identifier) => write!(f, "{}", identifier),
+            // A unit directly after a name would read as part of it, as in `xdeg`.
+            ExpressionKind::Unary(operator @ UnaryOperator::Angle(_), operand)
+                if matches!(
+                    operand.kind,
+                    ExpressionKind::Variable(_)
+                        | ExpressionKind::Unary(UnaryOperator::Angle(_), _)
+                ) =>
+            {
+                write!(f, "({}){}", operand, operator)
+            }
+            ExpressionKind::Unary(operator, operand) if operator.is_postfix() => {
+                write!(f, "{}{}", Operand(operand, operator.precedence()), operator)
+            }
+            ExpressionKind::Unary(operator, operand) => write!(f, "{}{}", operator, operand),
+            ExpressionKind::Binary(left, operator, _, right) => {
+                let left = Operand(left, operator.precedence()).to_string();
+                // A keyword operator needs spaces to not run into its operands, as does `==` after
+                // a factorial, `x!==y` reading as `x != =y`.
+                if *operator == Operator::BitXor || *operator == Operator::Equal && left.ends_with('!')
+                {
+                    write!(f, "({} {} {})", left, operator, right)
+                } else {
+                    write!(f, "({}{}{})", left, operator, right)
+                }
+            }
+            ExpressionKind::Call(identifier, arguments) => {
+                write!(f, "{}(", identifier)?;
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", argument)?;
+                }
+                write!(f, ")")
+            }
+            ExpressionKind::Conditional(condition, then, otherwise) => {
+                write!(f, "({}?{}:{})", condition, then, otherwise)
+            }
+            ExpressionKind::Invalid => write!(f, "<invalid>"),
+        }
+    }
+}
+
+/// Displays an operand followed by an operator of the given precedence, parenthesizing a prefix
+/// operation that would otherwise take the operator in, e.g. `(-2)^2` rather than `-2^2`.
+struct Operand<'a, N>(&'a Expression<N>, u8);
+
+impl<N: Display> Display for Operand<'_, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0.kind {
+            ExpressionKind::Unary(operator, _)
+                if !operator.is_postfix() && operator.precedence() <= self.1 =>
+            {
+                write!(f, "({})", self.0)
+            }
+            _ => write!(f, "{}", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod expression_tests {
+    use super::*;
+
+    fn literal(literal: f64) -> Expression {
+        Expression::new(ExpressionKind::Literal(literal), Span::new(0, 1))
+    }
+
+    fn variable(identifier: &str) -> Expression {
+        Expression::new(
+            ExpressionKind::Variable(identifier.to_string()),
+            Span::new(0, identifier.len()),
+        )
+    }
+
+    #[test]
+    fn binary_span() {
+        let left = Expression::new(ExpressionKind::Literal(1.0), Span::new(2, 3));
+        let right = Expression::new(ExpressionKind::Literal(2.0), Span::new(6, 7));
+        assert_eq!(Expression::binary(left.clone(), Operator::Add, Span::new(4, 5), right.clone()).span, Span::new(2, 7));
+        assert_eq!(Expression::conditional(left, literal(3.0), right).span, Span::new(2, 7));
+    }
+
+    #[test]
+    fn display() {
+        let operand = Expression::unary(UnaryOperator::Negate, literal(0.1), Span::new(0, 4));
+        assert_eq!(format!("{}", operand), "-0.1");
+        let sum = Expression::binary(operand.clone(), Operator::Add, Span::new(0, 1), variable("x_1"));
+        assert_eq!(format!("{}", sum), "(-0.1+x_1)");
+        let product = Expression::binary(sum.clone(), Operator::Multiply, Span::new(0, 1), literal(2.0));
+        assert_eq!(format!("{}", product), "((-0.1+x_1)*2)");
+        let call = Expression::new(
+            ExpressionKind::Call("max".to_string(), vec![sum, operand]),
+            Span::new(0, 1),
+        );
+        assert_eq!(format!("{}", call), "max((-0.1+x_1),-0.1)");
+        let factorial = Expression::unary(UnaryOperator::Factorial, product, Span::new(0, 1));
+        assert_eq!(format!("{}", factorial), "((-0.1+x_1)*2)!");
+        let xor = Expression::binary(variable("x"), Operator::BitXor, Span::new(0, 1), literal(2.0));
+        assert_eq!(format!("{}", xor), "(x xor 2)");
+        let conditional = Expression::conditional(variable("c"), xor, literal(1.0));
+        assert_eq!(format!("{}", conditional), "(c?(x xor 2):1)");
+        assert_eq!(
+            format!(
+                "{}",
+                Expression::<f64>::new(ExpressionKind::Call("f".to_string(), vec![]), Span::new(0, 3))
+            ),
+            "f()"
+        );
+    }
+
+    #[test]
+    fn substitute() {
+        let parameters = ["x".to_string(), "y".to_string()];
+        let call = |argument| {
+            Expression::new(
+                ExpressionKind::Call("f".to_string(), vec![argument]),
+                Span::new(0, 1),
+            )
+        };
+        let expression = Expression::conditional(
+            variable("x"),
+            Expression::binary(variable("x"), Operator::Add, Span::new(0, 1), call(variable("y"))),
+            Expression::unary(UnaryOperator::Negate, variable("z"), Span::new(0, 2)),
+        );
+        let substituted = expression.substitute(&parameters, &[1.0, 2.0]);
+        assert_eq!(format!("{}", substituted), "(1?(1+f(2)):-z)");
+        assert_eq!(substituted.span, expression.span);
+    }
+}
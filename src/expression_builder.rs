@@ -1,114 +1,529 @@
 use crate::{
-    expression_item::{ExpressionItem, Parentheses},
-    operator::Operator,
+    angle::AngleUnit,
+    context::EvalContext,
+    expression::{Expression, ExpressionKind},
+    function::Builtin,
+    lexer::{Lexer, Token, TokenKind},
+    number::{Number, MAX_EXPONENT},
+    operator::{Operator, UnaryOperator},
+    span::Span,
+    statement::{Statement, StatementKind},
 };
-use std::{error::Error, fmt::Display, iter::Peekable, str::Chars};
+use std::{collections::HashSet, error::Error, fmt::Display, marker::PhantomData};
 
-#[derive(Debug)]
-pub struct ExpressionBuilder<'a> {
-    chars: Peekable<Chars<'a>>,
+/// Deepest nesting of operations the builder accepts, so that neither building the tree nor
+/// evaluating it overflows the stack.
+pub const MAX_NESTING: usize = 256;
+
+/// Parses the tokens of an expression into a syntax tree holding operands of type `N`, using
+/// precedence climbing for the binary operators.
+#[derive(Debug, Clone)]
+pub struct ExpressionBuilder<N = f64> {
+    tokens: Vec<Token>,
+    index: usize,
+    /// Length of the source, errors at the end of the expression point just past it.
+    end: usize,
+    /// Errors recovered from so far, in the order they were found.
+    errors: Vec<ExpressionBuilderError>,
+    /// Nesting of the operation being read, bounded by `MAX_NESTING`.
+    depth: usize,
+    /// Names bound to numbers, followed by `(` they are multiplied rather than called.
+    variables: HashSet<String>,
+    /// Names of user functions, which take precedence over variables of the same name.
+    functions: HashSet<String>,
+    number: PhantomData<N>,
 }
 
-impl<'a> ExpressionBuilder<'a> {
-    pub fn new(expression: &'a str) -> Self {
+impl<N: Number> ExpressionBuilder<N> {
+    pub fn new(expression: &str) -> Self {
         Self {
-            chars: expression.chars().peekable(),
+            tokens: Lexer::new(expression).collect(),
+            index: 0,
+            end: expression.len(),
+            errors: Vec::new(),
+            depth: 0,
+            variables: HashSet::new(),
+            functions: HashSet::new(),
+            number: PhantomData,
+        }
+    }
+
+    /// Knows the variables and functions of `context` as well as those the source defines, so
+    /// with `x = 3` in the context `x(2)` is read as `x*(2)`.
+    pub fn with_context(mut self, context: &EvalContext<N>) -> Self {
+        self.variables.extend(context.variable_names().cloned());
+        self.functions.extend(context.function_names().cloned());
+        self
+    }
+
+    /// Whether `identifier` names a number rather than a function.
+    fn is_variable(&self, identifier: &str) -> bool {
+        self.variables.contains(identifier)
+            && !self.functions.contains(identifier)
+            && Builtin::get(identifier).is_none()
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.index).map(|token| &token.kind)
+    }
+
+    fn next_if(&mut self, func: impl FnOnce(&TokenKind) -> bool) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.index)
+            .filter(|token| func(&token.kind))?
+            .clone();
+        self.index += 1;
+        Some(token)
+    }
+
+    fn next_if_eq(&mut self, expected: &TokenKind) -> Option<Token> {
+        self.next_if(|kind| kind == expected)
+    }
+
+    /// Span of the token the builder is about to read, pointing just past the end of the
+    /// expression once every token has been consumed.
+    fn current_span(&self) -> Span {
+        self.tokens
+            .get(self.index)
+            .map_or(Span::new(self.end, self.end + 1), |token| token.span)
+    }
+
+    /// Reads a `+`, `-`, `~` or `!` prefixing any operand, e.g. `-2`, `-x` or `~(1+2)`.
+    fn get_prefix(&mut self) -> Option<UnaryOperator> {
+        let prefix = match self.peek()? {
+            TokenKind::Operator(Operator::Add) => UnaryOperator::Plus,
+            TokenKind::Operator(Operator::Subtract) => UnaryOperator::Negate,
+            TokenKind::UnaryOperator(operator) => operator.clone(),
+            TokenKind::Exclamation => UnaryOperator::Not,
+            _ => return None,
+        };
+        self.index += 1;
+        Some(prefix)
+    }
+
+    fn get_operator(&mut self) -> Option<Operator> {
+        let TokenKind::Operator(operator) = self.peek()? else {
+            return None;
+        };
+        let operator = operator.clone();
+        self.index += 1;
+        Some(operator)
+    }
+
+    /// The operator about to be read. An operand directly followed by `(` or a name, as in
+    /// `2(3+4)` or `3x`, is multiplied by it with the same precedence as `*`, so `1/2x` is
+    /// `(1/2)*x`. A number is only implicitly multiplied after a `)`, as in `(1+2)3`, since
+    /// `2 3` is more likely a typo.
+    fn peek_operator(&self) -> Option<Operator> {
+        let after_parenthesis = self
+            .index
+            .checked_sub(1)
+            .and_then(|index| self.tokens.get(index))
+            .is_some_and(|token| token.kind == TokenKind::RightParenthesis);
+        match self.peek()? {
+            TokenKind::Operator(operator) => Some(operator.clone()),
+            TokenKind::LeftParenthesis | TokenKind::Identifier(_) => Some(Operator::Multiply),
+            TokenKind::Number(_) if after_parenthesis => Some(Operator::Multiply),
+            _ => None,
         }
     }
 
-    fn get_operand(&mut self) -> Option<f32> {
-        let mut number_string = String::new();
-        if let Some(operator) = self.get_operator() {
-            number_string.push(char::from(&operator));
+    /// Counts one more level of nesting in the tree being built.
+    fn nest(&mut self) -> Result<(), ExpressionBuilderError> {
+        if self.depth >= MAX_NESTING {
+            return Err(ExpressionBuilderError::TooDeep(self.current_span()));
         }
-        while let Some(number_item) = self.chars.next_if(|number_item| {
-            number_item.is_ascii_digit() || (number_item == &'.' && !number_string.contains('.'))
-        }) {
-            number_string.push(number_item);
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Reads with `read` a level deeper, along with any nesting it adds along the way.
+    fn nested<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, ExpressionBuilderError>,
+    ) -> Result<T, ExpressionBuilderError> {
+        let depth = self.depth;
+        let result = self.nest().and_then(|_| read(self));
+        self.depth = depth;
+        result
+    }
+
+    fn get_identifier(&mut self) -> Option<String> {
+        let TokenKind::Identifier(identifier) = self.peek()? else {
+            return None;
+        };
+        let identifier = identifier.clone();
+        self.index += 1;
+        Some(identifier)
+    }
+
+    /// Reads a unit directly following an operand ending at `end`, as in `90deg` or `(pi/2)rad`.
+    /// Separated from it, as in `90 deg`, it is a variable instead.
+    fn get_angle_unit(&mut self, end: usize) -> Option<(AngleUnit, Span)> {
+        let token = self
+            .tokens
+            .get(self.index)
+            .filter(|token| token.span.start == end)?;
+        let TokenKind::Identifier(identifier) = &token.kind else {
+            return None;
+        };
+        let unit = identifier.parse().ok()?;
+        let span = token.span;
+        self.index += 1;
+        Some((unit, span))
+    }
+
+    fn get_literal(&mut self) -> Result<Option<Expression<N>>, ExpressionBuilderError> {
+        let span = self.current_span();
+        let Some(TokenKind::Number(number)) = self.peek() else {
+            return Ok(None);
+        };
+        let in_range = number.split_once(['e', 'E']).is_none_or(|(_, exponent)| {
+            exponent
+                .parse::<i64>()
+                .is_ok_and(|exponent| exponent.unsigned_abs() <= MAX_EXPONENT)
+        });
+        if !in_range {
+            return Err(ExpressionBuilderError::NumberOutOfRange(span));
         }
-        number_string.parse::<f32>().ok()
+        let literal = number
+            .parse::<N>()
+            .map_err(|_| ExpressionBuilderError::ExpectedOperand(span))?;
+        self.index += 1;
+        Ok(Some(Expression::new(ExpressionKind::Literal(literal), span)))
     }
 
-    fn get_operator(&mut self) -> Option<Operator> {
-        self.chars
-            .next_if(|character| matches!(character, '^' | '*' | '/' | '%' | '+' | '-'))
-            .and_then(|operator| Operator::new(&operator).ok())
+    /// Reads a single operand: a literal, a parenthesized expression, a variable or a call, along
+    /// with any factorial `!` or angle unit following it. A prefix binds looser than `^`, so `-2^2`
+    /// is `-(2^2)`, while a factorial binds tighter, so `2^3!` is `2^(3!)`. A `!` prefix negates a whole
+    /// comparison, `!x < 2` is `!(x<2)`.
+    fn get_primary(&mut self) -> Result<Expression<N>, ExpressionBuilderError> {
+        let span = self.current_span();
+        if let Some(operator) = self.get_prefix() {
+            let operand = self.get_binary(operator.precedence())?;
+            let span = Span::new(span.start, operand.span.end);
+            return Ok(Expression::unary(operator, operand, span));
+        }
+        let mut operand = if let Some(literal) = self.get_literal()? {
+            literal
+        } else if let Some(parentheses) = self.get_parentheses()? {
+            parentheses
+        } else {
+            self.get_identifier_or_call()?
+                .ok_or(ExpressionBuilderError::ExpectedOperand(span))?
+        };
+        loop {
+            let (operator, postfix) = if let Some(postfix) = self.next_if_eq(&TokenKind::Exclamation) {
+                (UnaryOperator::Factorial, postfix.span)
+            } else if let Some((unit, postfix)) = self.get_angle_unit(operand.span.end) {
+                (UnaryOperator::Angle(unit), postfix)
+            } else {
+                break;
+            };
+            self.nest()?;
+            let span = Span::new(operand.span.start, postfix.end);
+            operand = Expression::unary(operator, operand, span);
+        }
+        Ok(operand)
     }
 
-    fn skip_whitespace(&mut self) {
-        while self
-            .chars
-            .next_if(|character| character.is_ascii_whitespace())
-            .is_some()
-        {}
+    /// Reads operands joined by operators binding at least as strongly as `min_precedence`. Only
+    /// the operands of stronger and right associative operators nest, a chain such as `1+2+3` is
+    /// read in a loop whatever its length.
+    fn get_binary(&mut self, min_precedence: u8) -> Result<Expression<N>, ExpressionBuilderError> {
+        self.nested(|builder| {
+            let mut left = builder.get_primary()?;
+            loop {
+                let Some(operator) = builder
+                    .peek_operator()
+                    .filter(|operator| operator.precedence() >= min_precedence)
+                else {
+                    return Ok(left);
+                };
+                let span = builder.current_span();
+                // Consumes nothing for an implicit multiplication, which gets an empty span.
+                let operator_span = match builder.get_operator() {
+                    Some(_) => span,
+                    None => Span::new(span.start, span.start),
+                };
+                let right = if operator.is_right_associative() {
+                    builder.get_binary(operator.precedence())?
+                } else {
+                    builder.get_binary(operator.precedence() + 1)?
+                };
+                left = Expression::binary(left, operator, operator_span, right);
+            }
+        })
     }
 
-    fn get_next(
+    /// Reads a `condition ? then : otherwise` expression, or just the condition if no `?` follows
+    /// it. It binds looser than every operator and groups from the right, so
+    /// `a ? b : c ? d : e` is `a ? b : (c ? d : e)`.
+    fn get_conditional(&mut self) -> Result<Expression<N>, ExpressionBuilderError> {
+        let condition = self.get_binary(0)?;
+        if self.next_if_eq(&TokenKind::Question).is_none() {
+            return Ok(condition);
+        }
+        let then = self.nested(Self::get_conditional)?;
+        if self.next_if_eq(&TokenKind::Colon).is_none() {
+            return Err(ExpressionBuilderError::ExpectedColon(self.current_span()));
+        }
+        let otherwise = self.nested(Self::get_conditional)?;
+        Ok(Expression::conditional(condition, then, otherwise))
+    }
+
+    /// Reads an expression up to and including one of the `terminators`, `start` being the
+    /// position of the opening parentheses.
+    fn get_enclosed(
         &mut self,
-        vec_last: Option<&ExpressionItem>,
-    ) -> Result<ExpressionItem, ExpressionBuilderError> {
-        let expression_item: ExpressionItem = match vec_last {
-            None | Some(ExpressionItem::Operator(_)) => self
-                .get_operand()
-                .map_or(self.get_parentheses()?, |operand| {
-                    Some(ExpressionItem::from(&operand))
-                })
-                .ok_or(ExpressionBuilderError::ExpectedOperand)?,
-            Some(ExpressionItem::Operand(_)) | Some(ExpressionItem::Parentheses(_)) => {
-                ExpressionItem::from(
-                    self.get_operator()
-                        .ok_or(ExpressionBuilderError::ExpectedOperator)?,
-                )
+        start: usize,
+        terminators: &[TokenKind],
+    ) -> Result<(Expression<N>, Token), ExpressionBuilderError> {
+        let expression = self.get_conditional()?;
+        match self.next_if(|kind| terminators.contains(kind)) {
+            Some(terminator) => Ok((expression, terminator)),
+            None if self.peek().is_none() => Err(
+                ExpressionBuilderError::ExpectedClosingParentheses(Span::new(start, self.end)),
+            ),
+            None => Err(ExpressionBuilderError::ExpectedOperator(self.current_span())),
+        }
+    }
+
+    /// Records `err` and skips past the parentheses closing the group the builder is in, stopping
+    /// early at a `;` so the next statement is still read. Returns an `Invalid` expression spanning
+    /// from `start` to the end of the skipped tokens.
+    fn recover(&mut self, start: usize, err: ExpressionBuilderError) -> Expression<N> {
+        self.errors.push(err);
+        let mut depth = 0;
+        let mut end = self.current_span().start;
+        while let Some(token) = self.next_if(|kind| kind != &TokenKind::Semicolon) {
+            end = token.span.end;
+            match token.kind {
+                TokenKind::LeftParenthesis => depth += 1,
+                TokenKind::RightParenthesis if depth == 0 => break,
+                TokenKind::RightParenthesis => depth -= 1,
+                _ => {}
             }
+        }
+        Expression::new(ExpressionKind::Invalid, Span::new(start, end))
+    }
+
+    fn get_parentheses(&mut self) -> Result<Option<Expression<N>>, ExpressionBuilderError> {
+        let Some(opening) = self.next_if_eq(&TokenKind::LeftParenthesis) else {
+            return Ok(None);
         };
-        Ok(expression_item)
+        let start = opening.span.start;
+        Ok(Some(
+            match self.get_enclosed(start, &[TokenKind::RightParenthesis]) {
+                Ok((mut expression, closing)) => {
+                    expression.span = Span::new(start, closing.span.end);
+                    expression
+                }
+                Err(err) => self.recover(start, err),
+            },
+        ))
     }
 
-    fn get_parentheses(&mut self) -> Result<Option<ExpressionItem>, ExpressionBuilderError> {
-        let mut parentheses: Vec<ExpressionItem> = Vec::new();
-        if self.chars.next_if_eq(&'(').is_none() {
+    fn get_identifier_or_call(&mut self) -> Result<Option<Expression<N>>, ExpressionBuilderError> {
+        let span = self.current_span();
+        let Some(identifier) = self.get_identifier() else {
             return Ok(None);
+        };
+        // A variable followed by `(` is multiplied like any other operand, as in `3(2)`.
+        if self.is_variable(&identifier) || self.next_if_eq(&TokenKind::LeftParenthesis).is_none() {
+            return Ok(Some(Expression::new(
+                ExpressionKind::Variable(identifier),
+                span,
+            )));
+        }
+        let mut arguments = Vec::new();
+        let end = match self.next_if_eq(&TokenKind::RightParenthesis) {
+            Some(closing) => closing.span.end,
+            None => loop {
+                let (argument, terminator) = match self.get_enclosed(
+                    span.start,
+                    &[TokenKind::Comma, TokenKind::RightParenthesis],
+                ) {
+                    Ok(enclosed) => enclosed,
+                    Err(err) => return Ok(Some(self.recover(span.start, err))),
+                };
+                arguments.push(argument);
+                if terminator.kind == TokenKind::RightParenthesis {
+                    break terminator.span.end;
+                }
+            },
+        };
+        Ok(Some(Expression::new(
+            ExpressionKind::Call(identifier, arguments),
+            Span::new(span.start, end),
+        )))
+    }
+
+    /// Fails unless every token has been read.
+    fn expect_end(&self) -> Result<(), ExpressionBuilderError> {
+        match self.peek() {
+            Some(_) => Err(ExpressionBuilderError::ExpectedOperator(self.current_span())),
+            None => Ok(()),
         }
-        self.skip_whitespace();
-        while self.chars.next_if_eq(&')').is_none() {
-            self.skip_whitespace();
-            parentheses.push(self.get_next(parentheses.last())?);
-            if self.chars.peek().is_none() {
-                return Err(ExpressionBuilderError::ExpectedClosingParentheses);
+    }
+
+    /// Returns the first error recovered from, if any, as it comes before any error in `result`.
+    fn first_error<T>(
+        &mut self,
+        result: Result<T, ExpressionBuilderError>,
+    ) -> Result<T, ExpressionBuilderError> {
+        match std::mem::take(&mut self.errors).into_iter().next() {
+            Some(err) => Err(err),
+            None => result,
+        }
+    }
+
+    pub fn get_expression(&mut self) -> Result<Expression<N>, ExpressionBuilderError> {
+        let expression = self
+            .get_conditional()
+            .and_then(|expression| self.expect_end().map(|_| expression));
+        self.first_error(expression)
+    }
+
+    /// Reads a `(x, y)` parameter list, leaving the builder untouched if there is none.
+    fn get_parameters(&mut self) -> Option<Vec<String>> {
+        let read = |builder: &mut Self| {
+            builder.next_if_eq(&TokenKind::LeftParenthesis)?;
+            let mut parameters = Vec::new();
+            if builder.next_if_eq(&TokenKind::RightParenthesis).is_none() {
+                loop {
+                    parameters.push(builder.get_identifier()?);
+                    let terminator = builder.next_if(|kind| {
+                        matches!(kind, TokenKind::Comma | TokenKind::RightParenthesis)
+                    })?;
+                    if terminator.kind == TokenKind::RightParenthesis {
+                        break;
+                    }
+                }
+            }
+            Some(parameters)
+        };
+        let start = self.index;
+        let parameters = read(self);
+        if parameters.is_none() {
+            self.index = start;
+        }
+        parameters
+    }
+
+    /// Reads an expression ending at a `;` or the end of the source.
+    fn get_terminated(&mut self) -> Result<Expression<N>, ExpressionBuilderError> {
+        let expression = self.get_conditional()?;
+        match self.peek() {
+            None | Some(TokenKind::Semicolon) => Ok(expression),
+            Some(_) => Err(ExpressionBuilderError::ExpectedOperator(self.current_span())),
+        }
+    }
+
+    /// Reads a statement ending at a `;` or the end of the source, leaving the `;` unread.
+    fn get_terminated_statement(&mut self) -> Result<Statement<N>, ExpressionBuilderError> {
+        let start = self.index;
+        if let Some(identifier) = self.get_identifier() {
+            let parameters = self.get_parameters();
+            if self.next_if_eq(&TokenKind::Equals).is_some() {
+                let Some(parameters) = parameters else {
+                    let expression = self.get_terminated()?;
+                    self.variables.insert(identifier.clone());
+                    return Ok(Statement::new(StatementKind::Assignment(identifier, expression)));
+                };
+                self.functions.insert(identifier.clone());
+                // The parameters are variables within the body only.
+                let added = parameters
+                    .iter()
+                    .filter(|parameter| self.variables.insert(parameter.to_string()))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let expression = self.get_terminated();
+                for parameter in added {
+                    self.variables.remove(&parameter);
+                }
+                return Ok(Statement::new(StatementKind::Definition(
+                    identifier, parameters, expression?,
+                )));
             }
         }
-        Ok(Some(ExpressionItem::Parentheses(Parentheses::new(
-            parentheses,
-        ))))
+        self.index = start;
+        Ok(Statement::new(StatementKind::Expression(self.get_terminated()?)))
+    }
+
+    pub fn get_statement(&mut self) -> Result<Statement<N>, ExpressionBuilderError> {
+        let statement = self
+            .get_terminated_statement()
+            .and_then(|statement| self.expect_end().map(|_| statement));
+        self.first_error(statement)
     }
 
-    pub fn get_expression(&mut self) -> Result<Vec<ExpressionItem>, ExpressionBuilderError> {
-        let mut expressions: Vec<ExpressionItem> = vec![];
-        while self.chars.peek().is_some() {
-            expressions.push(self.get_next(expressions.last())?);
+    /// Reads statements separated by `;`, as in `x = 2; y = x^2`, a trailing `;` being allowed.
+    /// After an error it carries on past the closing parentheses of the group or the next `;`, so
+    /// every error in the source is returned, in order.
+    pub fn get_statements(&mut self) -> Result<Vec<Statement<N>>, Vec<ExpressionBuilderError>> {
+        let mut statements = Vec::new();
+        loop {
+            match self.get_terminated_statement() {
+                Ok(statement) => statements.push(statement),
+                Err(err) => {
+                    self.errors.push(err);
+                    while self.next_if(|kind| kind != &TokenKind::Semicolon).is_some() {}
+                }
+            }
+            if self.next_if_eq(&TokenKind::Semicolon).is_none() || self.peek().is_none() {
+                break;
+            }
         }
-        if matches!(expressions.last(), Some(ExpressionItem::Operator(_))) || expressions.last().is_none() {
-            return Err(ExpressionBuilderError::ExpectedOperand);
+        let errors = std::mem::take(&mut self.errors);
+        if errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(errors)
         }
-        Ok(expressions)
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ExpressionBuilderError {
-    ExpectedClosingParentheses,
-    ExpectedOperand,
-    ExpectedOperator,
+    ExpectedClosingParentheses(Span),
+    ExpectedOperand(Span),
+    ExpectedOperator(Span),
+    /// A `?` without the `:` separating its two branches.
+    ExpectedColon(Span),
+    /// A literal with an exponent larger than `MAX_EXPONENT`, as in `1e99999`.
+    NumberOutOfRange(Span),
+    /// Operations nested deeper than `MAX_NESTING`, pointing at the first one too deep.
+    TooDeep(Span),
+}
+
+impl ExpressionBuilderError {
+    pub fn span(&self) -> Span {
+        match self {
+            ExpressionBuilderError::ExpectedClosingParentheses(span)
+            | ExpressionBuilderError::ExpectedOperand(span)
+            | ExpressionBuilderError::ExpectedOperator(span)
+            | ExpressionBuilderError::ExpectedColon(span)
+            | ExpressionBuilderError::NumberOutOfRange(span)
+            | ExpressionBuilderError::TooDeep(span) => *span,
+        }
+    }
 }
 
 impl Display for ExpressionBuilderError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ExpressionBuilderError::ExpectedClosingParentheses => {
+            ExpressionBuilderError::ExpectedClosingParentheses(_) => {
                 write!(f, "Expected closing parentheses.")
             }
-            ExpressionBuilderError::ExpectedOperand => write!(f, "Expected operand."),
-            ExpressionBuilderError::ExpectedOperator => write!(f, "Expected operator."),
+            ExpressionBuilderError::ExpectedOperand(_) => write!(f, "Expected operand."),
+            ExpressionBuilderError::ExpectedOperator(_) => write!(f, "Expected operator."),
+            ExpressionBuilderError::ExpectedColon(_) => write!(f, "Expected colon."),
+            ExpressionBuilderError::NumberOutOfRange(_) => write!(f, "Number out of range."),
+            ExpressionBuilderError::TooDeep(_) => {
+                write!(f, "Expression nested deeper than {} levels.", MAX_NESTING)
+            }
         }
     }
 }
@@ -118,37 +533,49 @@ impl Error for ExpressionBuilderError {}
 #[cfg(test)]
 mod expresion_builder_tests {
     use super::*;
+    use bigdecimal::BigDecimal;
+    use proptest::prelude::*;
 
-    #[test]
-    fn get_operator() {
-        let operator_chars = &['+', '-', '*', '/', '^', '%'];
-        let operator_enums = operator_chars.map(|operator| Operator::new(&operator).unwrap());
-        for (operator_char, operator_enum) in operator_chars.iter().zip(operator_enums) {
-            assert!(ExpressionBuilder::new(&operator_char.to_string())
-                .get_operator()
-                .is_some_and(|operator| operator == operator_enum));
-        }
+    fn parse(expression: &str) -> Result<String, ExpressionBuilderError> {
+        ExpressionBuilder::<f64>::new(expression)
+            .get_expression()
+            .map(|expression| expression.to_string())
     }
 
     #[test]
-    fn get_operand() {
-        for sign in &['+', '-'] {
-            for number in 0..100u16 {
-                for rest in 0..100 {
-                    let expression = format!("{}{}.{}", sign, number, rest);
-                    assert!(ExpressionBuilder::new(&expression)
-                        .get_operand()
-                        .is_some_and(|operand| operand == expression.parse::<f32>().unwrap()));
-                }
-            }
+    fn get_literal() {
+        for (expression, literal) in [
+            ("1.5", 1.5),
+            ("1e-9", 1e-9),
+            ("6.02E23", 6.02e23),
+            ("0x1F", 31.0),
+            ("0b1010", 10.0),
+            ("0o17", 15.0),
+            ("1_000_000", 1_000_000.0),
+        ] {
+            assert!(ExpressionBuilder::<f64>::new(expression)
+                .get_literal()
+                .is_ok_and(|ok| ok.is_some_and(|some| some.kind == ExpressionKind::Literal(literal)
+                    && some.span == Span::new(0, expression.len()))), "{}", expression);
+        }
+        assert!(ExpressionBuilder::<f64>::new("x").get_literal().is_ok_and(|ok| ok.is_none()));
+        assert!(ExpressionBuilder::<f64>::new(".")
+            .get_literal()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(0, 1))));
+        for (expression, tree) in [
+            ("2e", "(2*e)"),
+            ("2e-x", "((2*e)-x)"),
+            ("0x", "(0*x)"),
+            ("0b2", "(0*b2)"),
+            ("1_x", "(1*_x)"),
+            ("2e3x", "(2000*x)"),
+        ] {
+            assert_eq!(parse(expression), Ok(tree.to_string()));
         }
-        assert!(ExpressionBuilder::new(".").get_operand().is_none());
-        assert!(ExpressionBuilder::new(".0")
-            .get_operand()
-            .is_some_and(|some| some == 0.0));
-        assert!(ExpressionBuilder::new("0.")
-            .get_operand()
-            .is_some_and(|some| some == 0.0));
+        assert!(ExpressionBuilder::<BigDecimal>::new("0x10000000000000000")
+            .get_literal()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.kind
+                == ExpressionKind::Literal("18446744073709551616".parse().unwrap()))));
     }
 
     struct F32Iterator {
@@ -197,141 +624,437 @@ mod expresion_builder_tests {
     }
 
     #[test]
-    fn get_next_operand() {
-        let operand = ExpressionItem::from(&1.0);
-        let operator = ExpressionItem::try_from('+').unwrap();
-        let parentheses = ExpressionItem::from(vec![
-            ExpressionItem::from(&1.0),
-            ExpressionItem::try_from('+').unwrap(),
-            ExpressionItem::from(&2.0),
-        ]);
-
-        assert!(ExpressionBuilder::new(&operand.to_string())
-            .get_next(None)
-            .is_ok_and(|ok| ok == operand));
-        assert!(ExpressionBuilder::new(&operand.to_string())
-            .get_next(Some(&operand))
-            .is_err_and(&|err| err == ExpressionBuilderError::ExpectedOperator));
-        assert!(ExpressionBuilder::new(&operand.to_string())
-            .get_next(Some(&operator))
-            .is_ok_and(|ok| ok == operand));
-        assert!(ExpressionBuilder::new(&operand.to_string())
-            .get_next(Some(&parentheses))
-            .is_err_and(&|err| err == ExpressionBuilderError::ExpectedOperator));
+    fn get_primary_literal() {
+        for operand in F32Iterator::new(10) {
+            let expression = operand.to_string();
+            assert!(ExpressionBuilder::<f32>::new(&expression)
+                .get_primary()
+                .is_ok_and(|ok| ok.to_string() == expression
+                    && ok.span == Span::new(0, expression.len())));
+        }
+        assert!(ExpressionBuilder::<f64>::new("+1.5")
+            .get_primary()
+            .is_ok_and(|ok| ok.kind
+                == ExpressionKind::Unary(
+                    UnaryOperator::Plus,
+                    Box::new(Expression::new(ExpressionKind::Literal(1.5), Span::new(1, 4)))
+                )));
+        assert!(ExpressionBuilder::<f64>::new(".")
+            .get_primary()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(0, 1))));
+        assert!(ExpressionBuilder::<f64>::new("-")
+            .get_primary()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(1, 2))));
     }
 
     #[test]
-    fn get_next_operator() {
-        let operand = ExpressionItem::from(&1.0);
-        let operator = ExpressionItem::try_from('+').unwrap();
-        let parentheses = ExpressionItem::from(vec![
-            ExpressionItem::from(&1.0),
-            ExpressionItem::try_from('+').unwrap(),
-            ExpressionItem::from(&2.0),
-        ]);
+    fn get_primary_unary() {
+        for (expression, tree) in [
+            ("-x", "-x"),
+            ("+x", "+x"),
+            ("--1", "--1"),
+            ("-(1+2)", "-(1+2)"),
+            ("-sqrt(4)", "-sqrt(4)"),
+            ("-x^2", "-(x^2)"),
+        ] {
+            assert!(ExpressionBuilder::<f64>::new(expression)
+                .get_primary()
+                .is_ok_and(|ok| ok.to_string() == tree && ok.span == Span::new(0, expression.len())));
+        }
+    }
 
-        assert!(ExpressionBuilder::new(&operator.to_string())
-            .get_next(None)
-            .is_err_and(&|err| err == ExpressionBuilderError::ExpectedOperand));
+    #[test]
+    fn get_parentheses() {
+        assert!(ExpressionBuilder::<f64>::new("(1+2)")
+            .get_parentheses()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.to_string() == "(1+2)"
+                && some.span == Span::new(0, 5))));
+        assert!(ExpressionBuilder::<f64>::new("((1+2)-2)")
+            .get_parentheses()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.to_string() == "((1+2)-2)")));
+        assert!(ExpressionBuilder::<f64>::new("( 1 )")
+            .get_parentheses()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.to_string() == "1")));
+        assert!(ExpressionBuilder::<f64>::new("1")
+            .get_parentheses()
+            .is_ok_and(|ok| ok.is_none()));
+        assert_eq!(
+            parse("(1"),
+            Err(ExpressionBuilderError::ExpectedClosingParentheses(Span::new(0, 2)))
+        );
+        assert_eq!(parse("()"), Err(ExpressionBuilderError::ExpectedOperand(Span::new(1, 2))));
+        assert_eq!(parse("(1 2)"), Err(ExpressionBuilderError::ExpectedOperator(Span::new(3, 4))));
+        let mut builder = ExpressionBuilder::<f64>::new("(1 2 (3))+4");
+        assert!(builder
+            .get_parentheses()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.kind == ExpressionKind::Invalid
+                && some.span == Span::new(0, 9))));
+        assert_eq!(builder.errors, vec![ExpressionBuilderError::ExpectedOperator(Span::new(3, 4))]);
+        assert_eq!(builder.peek(), Some(&TokenKind::Operator(Operator::Add)));
+    }
 
-        assert!(ExpressionBuilder::new(&operator.to_string())
-            .get_next(Some(&operand))
-            .is_ok_and(|ok| ok == operator));
+    #[test]
+    fn get_expression() {
+        for (expression, tree) in [
+            ("1", "1"),
+            ("1+2*3", "(1+(2*3))"),
+            ("1*2+3", "((1*2)+3)"),
+            ("1-2-3", "((1-2)-3)"),
+            ("8/4/2", "((8/4)/2)"),
+            ("2^3^2", "(2^(3^2))"),
+            ("-2^2", "-(2^2)"),
+            ("2^-2^2", "(2^-(2^2))"),
+            ("-2*3", "(-2*3)"),
+            ("1+-1", "(1+-1)"),
+            ("1--x", "(1--x)"),
+            ("2*-(1+1)", "(2*-(1+1))"),
+            ("(1+2)*3", "((1+2)*3)"),
+            ("2+2-2*2/2%2^2", "((2+2)-(((2*2)/2)%(2^2)))"),
+            ("max(1, x-2)*2", "(max(1,(x-2))*2)"),
+            ("2(3+4)", "(2*(3+4))"),
+            ("(1+2)(3+4)", "((1+2)*(3+4))"),
+            ("3x", "(3*x)"),
+            ("2x^2", "(2*(x^2))"),
+            ("-2x", "(-2*x)"),
+            ("1/2x", "((1/2)*x)"),
+            ("2^3x", "((2^3)*x)"),
+            ("2sin(x)y", "((2*sin(x))*y)"),
+            ("(1 (2))", "(1*2)"),
+            ("7//2*3", "((7//2)*3)"),
+            ("1|2 xor 3&4", "(1|(2 xor (3&4)))"),
+            ("1&2<<3", "(1&(2<<3))"),
+            ("1<<2+3", "(1<<(2+3))"),
+            ("8>>1>>1", "((8>>1)>>1)"),
+            ("~1+2", "(~1+2)"),
+            ("~x^2", "~(x^2)"),
+            ("3!", "3!"),
+            ("3!!", "3!!"),
+            ("-3!", "-3!"),
+            ("2^3!", "(2^3!)"),
+            ("(1+2)!x", "((1+2)!*x)"),
+            ("1+2<3*4", "((1+2)<(3*4))"),
+            ("x|1==y&1", "((x|1)==(y&1))"),
+            ("1<2&&2<3||x", "(((1<2)&&(2<3))||x)"),
+            ("a||b&&c", "(a||(b&&c))"),
+            ("!x<2&&y", "(!(x<2)&&y)"),
+            ("!!x", "!!x"),
+            ("3!=6", "(3!=6)"),
+            ("3!!=6", "(3!!=6)"),
+            ("x>=-1", "(x>=-1)"),
+            ("x<0?-x:x", "((x<0)?-x:x)"),
+            ("a?b:c?d:e", "(a?b:(c?d:e))"),
+            ("a?b?c:d:e", "(a?(b?c:d):e)"),
+            ("1+(a?2:3)*4", "(1+((a?2:3)*4))"),
+            ("a||b?1+2:f(c?1:2)", "((a||b)?(1+2):f((c?1:2)))"),
+            ("(-2)^2", "((-2)^2)"),
+            ("(-3)!", "(-3)!"),
+            ("(!x)<2", "((!x)<2)"),
+            ("(3!)==6", "(3! == 6)"),
+            ("-3! == 6", "(-3! == 6)"),
+            ("90deg", "90deg"),
+            ("-90deg", "-90deg"),
+            ("sin(90deg)", "sin(90deg)"),
+            ("2^90deg", "(2^90deg)"),
+            ("(pi/2)rad+x", "((pi/2)rad+x)"),
+            ("(x)grad", "(x)grad"),
+            ("(90deg)rad", "(90deg)rad"),
+            ("3!deg", "3!deg"),
+            ("90 deg", "(90*deg)"),
+            ("2degx", "(2*degx)"),
+        ] {
+            assert_eq!(parse(expression), Ok(tree.to_string()));
+        }
+    }
 
-        assert!(ExpressionBuilder::new(&operator.to_string())
-            .get_next(Some(&operator))
-            .is_err_and(&|err| err == ExpressionBuilderError::ExpectedOperand));
+    #[test]
+    fn get_expression_whitespace() {
+        for (expression, tree) in [
+            (" 1 + 2 ", "(1+2)"),
+            ("\t1\n*\t2\n", "(1*2)"),
+            ("\r\n 2 ^ 3 ^ 2 ", "(2^(3^2))"),
+            ("- ( 1 )", "-1"),
+            ("sqrt (4) * max ( 1 , 2 )", "(sqrt(4)*max(1,2))"),
+            ("2 x", "(2*x)"),
+            ("x y", "(x*y)"),
+        ] {
+            assert_eq!(parse(expression), Ok(tree.to_string()));
+        }
+        assert_eq!(parse("1 + "), Err(ExpressionBuilderError::ExpectedOperand(Span::new(4, 5))));
+        assert_eq!(parse("1 2"), Err(ExpressionBuilderError::ExpectedOperator(Span::new(2, 3))));
+        assert_eq!(
+            parse(" (1 "),
+            Err(ExpressionBuilderError::ExpectedClosingParentheses(Span::new(1, 4)))
+        );
+        assert!(ExpressionBuilder::<f64>::new(" x ")
+            .get_expression()
+            .is_ok_and(|ok| ok.span == Span::new(1, 2)));
+    }
 
-        assert!(ExpressionBuilder::new(&operator.to_string())
-            .get_next(Some(&parentheses))
-            .is_ok_and(|ok| ok == operator));
+    #[test]
+    fn get_expression_span() {
+        assert!(ExpressionBuilder::<f64>::new("(1+2)*x")
+            .get_expression()
+            .is_ok_and(|ok| ok.span == Span::new(0, 7)
+                && matches!(ok.kind, ExpressionKind::Binary(left, _, operator, right)
+                    if left.span == Span::new(0, 5)
+                        && operator == Span::new(5, 6)
+                        && right.span == Span::new(6, 7))));
+        assert!(ExpressionBuilder::<f64>::new("2 (x)")
+            .get_expression()
+            .is_ok_and(|ok| matches!(ok.kind, ExpressionKind::Binary(_, _, operator, _)
+                if operator == Span::new(2, 2))));
+        assert!(ExpressionBuilder::<f64>::new("1+90deg")
+            .get_expression()
+            .is_ok_and(|ok| matches!(ok.kind, ExpressionKind::Binary(_, _, _, right)
+                if right.span == Span::new(2, 7))));
     }
 
     #[test]
-    fn get_next_parentheses() {
-        let operand = ExpressionItem::from(&1.0);
-        let operator = ExpressionItem::try_from('+').unwrap();
-        let parentheses = ExpressionItem::from(vec![
-            ExpressionItem::from(&1.0),
-            ExpressionItem::try_from('+').unwrap(),
-            ExpressionItem::from(&2.0),
-        ]);
-
-        assert!(ExpressionBuilder::new(&parentheses.to_string())
-            .get_next(None)
-            .is_ok_and(|ok| ok == parentheses));
-        assert!(ExpressionBuilder::new(&parentheses.to_string())
-            .get_next(Some(&operand))
-            .is_err_and(&|err| err == ExpressionBuilderError::ExpectedOperator));
-        assert!(ExpressionBuilder::new(&parentheses.to_string())
-            .get_next(Some(&operator))
-            .is_ok_and(|ok| ok == parentheses));
-        assert!(ExpressionBuilder::new(&parentheses.to_string())
-            .get_next(Some(&parentheses))
-            .is_err_and(&|err| err == ExpressionBuilderError::ExpectedOperator));
+    fn get_expression_err_span() {
+        assert!(ExpressionBuilder::<f64>::new("")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(0, 1))));
+        assert!(ExpressionBuilder::<f64>::new("1+2+")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(4, 5))));
+        assert!(ExpressionBuilder::<f64>::new("1+2*/3")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(4, 5))));
+        assert!(ExpressionBuilder::<f64>::new("2 3")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperator(Span::new(2, 3))));
+        assert!(ExpressionBuilder::<f64>::new("1+(2*(3)")
+            .get_expression()
+            .is_err_and(|err| err
+                == ExpressionBuilderError::ExpectedClosingParentheses(Span::new(2, 8))));
+        assert!(ExpressionBuilder::<f64>::new("1$2")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperator(Span::new(1, 2))));
+        assert!(ExpressionBuilder::<f64>::new("a?1")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedColon(Span::new(3, 4))));
+        assert!(ExpressionBuilder::<f64>::new("a?1 2:3")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedColon(Span::new(4, 5))));
+        assert!(ExpressionBuilder::<f64>::new("a?:3")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(2, 3))));
+        assert!(ExpressionBuilder::<f64>::new("a:3")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperator(Span::new(1, 2))));
+        assert!(ExpressionBuilder::<f64>::new("2*é")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(2, 4))));
     }
 
     #[test]
-    fn get_parentheses_operands_operators() {
-        let mut parentheses: Vec<ExpressionItem> = Vec::new();
-        for operator in &['+', '-', '*', '/', '^', '%'] {
-            for operand in F32Iterator::new(10) {
-                parentheses.push(ExpressionItem::from(&operand));
-                let parentheses_item = ExpressionItem::from(parentheses.clone());
-                assert!(ExpressionBuilder::new(&format!("{}", &parentheses_item))
-                    .get_parentheses()
-                    .is_ok_and(|ok| ok.is_some_and(|some| { some == parentheses_item })));
-                parentheses.push(Operator::new(operator).unwrap().into());
-            }
+    fn get_expression_limits() {
+        let too_deep = |expression: &str| {
+            ExpressionBuilder::<f64>::new(expression)
+                .get_expression()
+                .is_err_and(|err| matches!(err, ExpressionBuilderError::TooDeep(_)))
+        };
+        for expression in [
+            format!("1{}", "+1".repeat(MAX_NESTING / 2)),
+            format!("{}1{}", "(".repeat(MAX_NESTING / 2), ")".repeat(MAX_NESTING / 2)),
+            format!("{}1", "-".repeat(MAX_NESTING / 2)),
+            format!("1{}", "!".repeat(MAX_NESTING / 2)),
+        ] {
+            assert!(ExpressionBuilder::<f64>::new(&expression).get_expression().is_ok());
         }
+        assert!(ExpressionBuilder::<f64>::new(&format!("1{}", "+1".repeat(MAX_NESTING * 4)))
+            .get_expression()
+            .is_ok());
+        assert!(too_deep(&format!("1{}", "^1".repeat(MAX_NESTING))));
+        assert!(too_deep(&format!("{}1{}", "(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING))));
+        assert!(too_deep(&format!("{}1", "-".repeat(MAX_NESTING))));
+        assert!(too_deep(&format!("1{}", "!".repeat(MAX_NESTING))));
+        assert!(too_deep(&format!("{}1{}", "1?".repeat(MAX_NESTING), ":1".repeat(MAX_NESTING))));
+        assert!(ExpressionBuilder::<f64>::new("1e-65536")
+            .get_expression()
+            .is_ok_and(|expression| expression.kind == ExpressionKind::Literal(0.0)));
+        assert!(ExpressionBuilder::<f64>::new("2*1e70000")
+            .get_expression()
+            .is_err_and(|err| err == ExpressionBuilderError::NumberOutOfRange(Span::new(2, 9))));
     }
 
     #[test]
-    fn get_parentheses() {
-        let parentheses = ExpressionItem::from(vec![
-            ExpressionItem::from(&1.0),
-            ExpressionItem::try_from('+').unwrap(),
-            ExpressionItem::from(&2.0),
-        ]);
-        assert!(ExpressionBuilder::new(&format!("{}", parentheses))
-            .get_parentheses()
-            .is_ok_and(|ok| ok.is_some_and(|some| some == parentheses)));
-        let expression_item = ExpressionItem::from(vec![
-            parentheses,
-            ExpressionItem::try_from('-').unwrap(),
-            ExpressionItem::from(&2.0),
-        ]);
-        assert!(ExpressionBuilder::new(&format!("{}", expression_item))
-            .get_parentheses()
-            .is_ok_and(|ok| ok.is_some_and(|some| some == expression_item)));
+    fn get_statement() {
+        assert!(ExpressionBuilder::<f64>::new("x = y*2")
+            .get_statement()
+            .is_ok_and(|ok| matches!(&ok.kind, StatementKind::Assignment(..)) && ok.to_string() == "x=(y*2)"));
+        assert!(ExpressionBuilder::<f64>::new("y*2")
+            .get_statement()
+            .is_ok_and(|ok| matches!(&ok.kind, StatementKind::Expression(..)) && ok.to_string() == "(y*2)"));
+        assert!(ExpressionBuilder::<f64>::new("x =")
+            .get_statement()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperand(Span::new(3, 4))));
+        assert!(ExpressionBuilder::<f64>::new("f( x,y ) = y*2")
+            .get_statement()
+            .is_ok_and(|ok| matches!(&ok.kind, StatementKind::Definition(..))
+                && ok.to_string() == "f(x,y)=(y*2)"));
+        assert!(ExpressionBuilder::<f64>::new("f(1)=y*2")
+            .get_statement()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperator(Span::new(4, 5))));
+        assert!(ExpressionBuilder::<f64>::new("x 2")
+            .get_statement()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperator(Span::new(2, 3))));
+        assert!(ExpressionBuilder::<f64>::new("  x\t=\n1 ")
+            .get_statement()
+            .is_ok_and(|ok| matches!(&ok.kind, StatementKind::Assignment(..)) && ok.to_string() == "x=1"));
+        assert!(ExpressionBuilder::<f64>::new("f(x) = x < 0 ? -x : x")
+            .get_statement()
+            .is_ok_and(|ok| ok.to_string() == "f(x)=((x<0)?-x:x)"));
+        assert!(ExpressionBuilder::<f64>::new(" f (x) = x ")
+            .get_statement()
+            .is_ok_and(|ok| matches!(&ok.kind, StatementKind::Definition(..)) && ok.to_string() == "f(x)=x"));
+    }
 
-        let single_vec_expression = ExpressionItem::from(vec![ExpressionItem::from(&1.0)]);
-        assert!(
-            ExpressionBuilder::new(&format!("{}", single_vec_expression))
-                .get_parentheses()
-                .is_ok_and(|ok| ok.is_some_and(|some| some == single_vec_expression))
+    #[test]
+    fn get_call() {
+        assert!(ExpressionBuilder::<f64>::new("max(1, x-2)")
+            .get_identifier_or_call()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.to_string() == "max(1,(x-2))"
+                && some.span == Span::new(0, 11))));
+        assert!(ExpressionBuilder::<f64>::new("max(1,(x-2))")
+            .get_identifier_or_call()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.to_string() == "max(1,(x-2))")));
+        assert!(ExpressionBuilder::<f64>::new("f()")
+            .get_identifier_or_call()
+            .is_ok_and(|ok| ok.is_some_and(|some| some.kind
+                == ExpressionKind::Call("f".to_string(), vec![]))));
+        assert_eq!(parse("f(1,,2)"), Err(ExpressionBuilderError::ExpectedOperand(Span::new(4, 5))));
+        assert_eq!(
+            parse("f(1"),
+            Err(ExpressionBuilderError::ExpectedClosingParentheses(Span::new(0, 3)))
         );
-        assert!(
-            ExpressionBuilder::new("(1")
-                .get_parentheses()
-                .is_err_and(|err| err == ExpressionBuilderError::ExpectedClosingParentheses)
+    }
+
+    #[test]
+    fn get_statements() {
+        let statements = |source: &str| {
+            ExpressionBuilder::<f64>::new(source)
+                .get_statements()
+                .map(|statements| {
+                    statements
+                        .iter()
+                        .map(|statement| statement.to_string())
+                        .collect::<Vec<String>>()
+                })
+        };
+        assert_eq!(statements("1+2"), Ok(vec!["(1+2)".to_string()]));
+        assert_eq!(
+            statements("x = 2; f(y) = x*y;f(3);"),
+            Ok(vec!["x=2".to_string(), "f(y)=(x*y)".to_string(), "f(3)".to_string()])
+        );
+        assert_eq!(
+            statements("(1+)*(2*)"),
+            Err(vec![
+                ExpressionBuilderError::ExpectedOperand(Span::new(3, 4)),
+                ExpressionBuilderError::ExpectedOperand(Span::new(8, 9)),
+            ])
+        );
+        assert_eq!(
+            statements("1+; 2 3; (4; x = max(1,,2) + 5)"),
+            Err(vec![
+                ExpressionBuilderError::ExpectedOperand(Span::new(2, 3)),
+                ExpressionBuilderError::ExpectedOperator(Span::new(6, 7)),
+                ExpressionBuilderError::ExpectedOperator(Span::new(11, 12)),
+                ExpressionBuilderError::ExpectedOperand(Span::new(23, 24)),
+                ExpressionBuilderError::ExpectedOperator(Span::new(30, 31)),
+            ])
+        );
+        assert_eq!(
+            statements(""),
+            Err(vec![ExpressionBuilderError::ExpectedOperand(Span::new(0, 1))])
+        );
+        assert_eq!(
+            statements("1;;2"),
+            Err(vec![ExpressionBuilderError::ExpectedOperand(Span::new(2, 3))])
         );
+        assert!(ExpressionBuilder::<f64>::new("1;2")
+            .get_statement()
+            .is_err_and(|err| err == ExpressionBuilderError::ExpectedOperator(Span::new(1, 2))));
     }
 
     #[test]
-    fn get_expression() {
-        let parentheses = ExpressionItem::from(vec![
-            ExpressionItem::from(&1.0),
-            ExpressionItem::try_from('+').unwrap(),
-            ExpressionItem::from(&2.0),
-        ]);
-        let expression_item = ExpressionItem::from(vec![
-            parentheses,
-            ExpressionItem::try_from('-').unwrap(),
-            ExpressionItem::from(&2.0),
-        ]);
-        assert!(ExpressionBuilder::new(&format!("{}", expression_item))
-            .get_expression()
-            .is_ok_and(|ok| ok == vec![expression_item]));
+    fn get_statements_variables() {
+        let statements = |source: &str, context: &EvalContext<f64>| {
+            ExpressionBuilder::<f64>::new(source)
+                .with_context(context)
+                .get_statements()
+                .map(|statements| statements.iter().map(Statement::to_string).collect::<Vec<_>>())
+        };
+        let mut context = EvalContext::new();
+        assert_eq!(
+            statements("x = 3; x(2)^2; f(x) = x(2); f(1); y(2)", &context),
+            Ok(vec![
+                "x=3".to_string(),
+                "(x*(2^2))".to_string(),
+                "f(x)=(x*2)".to_string(),
+                "f(1)".to_string(),
+                "y(2)".to_string(),
+            ])
+        );
+        context.set("y", 2.0);
+        assert_eq!(
+            statements("y(2); sin = 1; sin(0)", &context),
+            Ok(vec!["(y*2)".to_string(), "sin=1".to_string(), "sin(0)".to_string()])
+        );
+        assert_eq!(
+            statements("y(x) = 1; y(2)", &context),
+            Ok(vec!["y(x)=1".to_string(), "y(2)".to_string()])
+        );
+    }
+
+    const OPERATORS: [&str; 20] = [
+        "+", "-", "*", "/", "^", "%", "//", "&", "|", "xor", "<<", ">>", "<", "<=", "==", "!=",
+        ">", ">=", "&&", "||",
+    ];
+
+    /// Sources built from the grammar, which random characters rarely follow.
+    fn source() -> impl Strategy<Value = String> {
+        let leaf = prop_oneof![
+            (0u32..1000).prop_map(|integer| integer.to_string()),
+            r"[0-9]{1,3}\.[0-9]{1,3}",
+            r"[1-9]e-?[0-9]{1,2}",
+            r"0x[0-9a-f]{1,4}",
+            prop::sample::select(vec!["x", "pi", "e", "y2"]).prop_map(str::to_string),
+        ];
+        leaf.prop_recursive(6, 64, 3, |inner| {
+            prop_oneof![
+                (inner.clone(), prop::sample::select(&OPERATORS[..]), inner.clone())
+                    .prop_map(|(left, operator, right)| format!("{} {} {}", left, operator, right)),
+                (inner.clone(), inner.clone()).prop_map(|(left, right)| format!("{}({})", left, right)),
+                (prop::sample::select(vec!["-", "+", "~", "!"]), inner.clone())
+                    .prop_map(|(prefix, operand)| format!("{}{}", prefix, operand)),
+                inner.clone().prop_map(|operand| format!("({})!", operand)),
+                (inner.clone(), prop::sample::select(vec!["deg", "rad", "grad"]))
+                    .prop_map(|(operand, unit)| format!("({}){}", operand, unit)),
+                (
+                    "[a-z]{1,4}".prop_filter("Keyword", |identifier| identifier != "xor"),
+                    prop::collection::vec(inner.clone(), 0..3),
+                )
+                    .prop_map(|(identifier, arguments)| format!("{}({})", identifier, arguments.join(", "))),
+                (inner.clone(), inner.clone(), inner)
+                    .prop_map(|(condition, then, otherwise)| format!("{} ? {} : {}", condition, then, otherwise)),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn get_expression_never_panics(source in any::<String>()) {
+            let _ = ExpressionBuilder::<f64>::new(&source).get_statements();
+            let _ = ExpressionBuilder::<BigDecimal>::new(&source).get_expression();
+        }
+
+        #[test]
+        fn display_reparses(source in source()) {
+            let displayed = parse(&source);
+            prop_assert!(displayed.is_ok(), "{} fails with {:?}", source, displayed);
+            let displayed = displayed.unwrap();
+            prop_assert_eq!(parse(&displayed), Ok(displayed.clone()), "{}", source);
+        }
     }
 }
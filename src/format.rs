@@ -0,0 +1,102 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use crate::{number::Number, value::Value};
+
+/// How results are written. Non-integers fall back to `Decimal` in the integer-only formats.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum OutputFormat {
+    #[default]
+    Decimal,
+    /// Mantissa and exponent as in `6.02e23`, exact for the exact number types.
+    Scientific,
+    Hexadecimal,
+    Binary,
+    Octal,
+}
+
+impl OutputFormat {
+    pub fn format<N: Number>(&self, number: &N) -> String {
+        match self {
+            OutputFormat::Decimal => None,
+            OutputFormat::Scientific => Some(number.to_scientific()),
+            OutputFormat::Hexadecimal => number.to_integer().map(|integer| format!("{:#x}", integer)),
+            OutputFormat::Binary => number.to_integer().map(|integer| format!("{:#b}", integer)),
+            OutputFormat::Octal => number.to_integer().map(|integer| format!("{:#o}", integer)),
+        }
+        .unwrap_or_else(|| number.to_string())
+    }
+
+    pub fn format_value<N: Number>(&self, value: &Value<N>) -> String {
+        match value {
+            Value::Number(number) => self.format(number),
+            Value::Boolean(boolean) => boolean.to_string(),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = OutputFormatError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "decimal" | "dec" => Ok(OutputFormat::Decimal),
+            "scientific" | "sci" => Ok(OutputFormat::Scientific),
+            "hexadecimal" | "hex" => Ok(OutputFormat::Hexadecimal),
+            "binary" | "bin" => Ok(OutputFormat::Binary),
+            "octal" | "oct" => Ok(OutputFormat::Octal),
+            format => Err(OutputFormatError::UnknownFormat(format.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OutputFormatError {
+    UnknownFormat(String),
+}
+
+impl Display for OutputFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormatError::UnknownFormat(format) => write!(
+                f,
+                "Unknown format {}, expected decimal, scientific, hex, binary or octal.",
+                format
+            ),
+        }
+    }
+}
+
+impl Error for OutputFormatError {}
+
+#[cfg(test)]
+mod format_tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    #[test]
+    fn format() {
+        assert_eq!(OutputFormat::Decimal.format(&31.0), "31");
+        assert_eq!(OutputFormat::Scientific.format(&6.02e23), "6.02e23");
+        assert_eq!(OutputFormat::Scientific.format(&0.000000001), "1e-9");
+        assert_eq!(OutputFormat::Hexadecimal.format(&31.0), "0x1f");
+        assert_eq!(OutputFormat::Hexadecimal.format(&-31.0), "-0x1f");
+        assert_eq!(OutputFormat::Binary.format(&10.0), "0b1010");
+        assert_eq!(OutputFormat::Octal.format(&15.0), "0o17");
+        assert_eq!(OutputFormat::Hexadecimal.format(&1.5), "1.5");
+        let decimal = "18446744073709551615".parse::<BigDecimal>().unwrap();
+        assert_eq!(OutputFormat::Hexadecimal.format(&decimal), "0xffffffffffffffff");
+        assert_eq!(OutputFormat::Scientific.format(&decimal), "1.8446744073709551615e19");
+        assert_eq!(OutputFormat::Binary.format_value(&Value::Number(10.0)), "0b1010");
+        assert_eq!(OutputFormat::Binary.format_value(&Value::<f64>::Boolean(true)), "true");
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("hex".parse(), Ok(OutputFormat::Hexadecimal));
+        assert_eq!("scientific".parse(), Ok(OutputFormat::Scientific));
+        assert_eq!(
+            "roman".parse::<OutputFormat>(),
+            Err(OutputFormatError::UnknownFormat("roman".to_string()))
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt::Display;
+
+use crate::{
+    angle::AngleUnit,
+    eval::EvalError,
+    expression::Expression,
+    number::Number,
+    span::Span,
+};
+
+/// Number of arguments a function accepts, `max` is `None` for variadic functions.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    pub const fn exact(count: usize) -> Self {
+        Self {
+            min: count,
+            max: Some(count),
+        }
+    }
+
+    pub const fn range(min: usize, max: usize) -> Self {
+        Self {
+            min,
+            max: Some(max),
+        }
+    }
+
+    pub const fn at_least(min: usize) -> Self {
+        Self { min, max: None }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize| if count == 1 { "argument" } else { "arguments" };
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{} {}", max, plural(max)),
+            Some(max) => write!(f, "{} to {} {}", self.min, max, plural(max)),
+            None => write!(f, "at least {} {}", self.min, plural(self.min)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BuiltinFunction {
+    /// Computed in floating point, exact number types can't represent the result.
+    Float(fn(&[f64]) -> f64),
+    /// The root of the given degree, exact when the number type is.
+    Root(u32),
+    /// A float function of an angle, given in the context's unit.
+    OfAngle(fn(f64) -> f64),
+    /// A float function giving an angle, returned in the context's unit.
+    Angle(fn(&[f64]) -> f64),
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    Min,
+    Max,
+}
+
+#[derive(Debug)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: Arity,
+    function: BuiltinFunction,
+}
+
+impl Builtin {
+    pub fn get(name: &str) -> Option<&'static Builtin> {
+        BUILTINS.iter().find(|builtin| builtin.name == name)
+    }
+
+    /// Whether the function is only computed in floating point.
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self.function,
+            BuiltinFunction::Float(_) | BuiltinFunction::OfAngle(_) | BuiltinFunction::Angle(_)
+        )
+    }
+
+    /// Applies the function, failing unless its arity accepts the number of `arguments`. `span`
+    /// covers the call and angles are measured in `angle_unit`.
+    pub fn call<N: Number>(
+        &self,
+        arguments: &[N],
+        span: Span,
+        angle_unit: AngleUnit,
+    ) -> Result<N, EvalError> {
+        if !self.arity.accepts(arguments.len()) {
+            return Err(EvalError::WrongArgumentCount {
+                function: self.name.to_string(),
+                expected: self.arity,
+                found: arguments.len(),
+                span,
+            });
+        }
+        let select = |pick: fn(&N, &N) -> bool| {
+            arguments
+                .iter()
+                .fold(&arguments[0], |selected, argument| {
+                    if pick(argument, selected) {
+                        argument
+                    } else {
+                        selected
+                    }
+                })
+                .clone()
+        };
+        let floats = || arguments.iter().map(N::to_f64).collect::<Vec<f64>>();
+        if N::EXACT && self.is_float() {
+            return Err(EvalError::FloatFunction(self.name.to_string(), span));
+        }
+        let result = match self.function {
+            BuiltinFunction::Float(function) => N::from_f64(function(&floats())),
+            BuiltinFunction::OfAngle(function) => {
+                N::from_f64(function(angle_unit.to_radians(arguments[0].to_f64())))
+            }
+            BuiltinFunction::Angle(function) => {
+                N::from_f64(angle_unit.from_radians(function(&floats())))
+            }
+            BuiltinFunction::Root(degree) => arguments[0].root(degree),
+            BuiltinFunction::Abs => Ok(arguments[0].abs()),
+            BuiltinFunction::Floor => Ok(arguments[0].floor()),
+            BuiltinFunction::Ceil => Ok(arguments[0].ceil()),
+            BuiltinFunction::Round => Ok(arguments[0].round()),
+            BuiltinFunction::Min => Ok(select(|argument, selected| argument < selected)),
+            BuiltinFunction::Max => Ok(select(|argument, selected| argument > selected)),
+        };
+        result.map_err(|err| EvalError::NumberError(err, span))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UserFunction<N = f64> {
+    pub parameters: Vec<String>,
+    pub body: Expression<N>,
+}
+
+impl<N: Number> UserFunction<N> {
+    pub fn new(parameters: Vec<String>, body: Expression<N>) -> Self {
+        Self { parameters, body }
+    }
+
+    pub fn arity(&self) -> Arity {
+        Arity::exact(self.parameters.len())
+    }
+
+    /// The body with the parameters replaced by `arguments`, ready to be evaluated.
+    pub fn apply(&self, arguments: &[N]) -> Expression<N> {
+        self.body.substitute(&self.parameters, arguments)
+    }
+}
+
+const fn unary(name: &'static str, function: BuiltinFunction) -> Builtin {
+    Builtin {
+        name,
+        arity: Arity::exact(1),
+        function,
+    }
+}
+
+const fn float(name: &'static str, function: fn(&[f64]) -> f64) -> Builtin {
+    unary(name, BuiltinFunction::Float(function))
+}
+
+const fn of_angle(name: &'static str, function: fn(f64) -> f64) -> Builtin {
+    unary(name, BuiltinFunction::OfAngle(function))
+}
+
+const fn angle(name: &'static str, function: fn(&[f64]) -> f64) -> Builtin {
+    unary(name, BuiltinFunction::Angle(function))
+}
+
+pub const BUILTINS: &[Builtin] = &[
+    of_angle("sin", f64::sin),
+    of_angle("cos", f64::cos),
+    of_angle("tan", f64::tan),
+    angle("asin", |arguments| arguments[0].asin()),
+    angle("acos", |arguments| arguments[0].acos()),
+    angle("atan", |arguments| arguments[0].atan()),
+    float("sinh", |arguments| arguments[0].sinh()),
+    float("cosh", |arguments| arguments[0].cosh()),
+    float("tanh", |arguments| arguments[0].tanh()),
+    unary("sqrt", BuiltinFunction::Root(2)),
+    unary("cbrt", BuiltinFunction::Root(3)),
+    float("exp", |arguments| arguments[0].exp()),
+    float("ln", |arguments| arguments[0].ln()),
+    unary("abs", BuiltinFunction::Abs),
+    unary("floor", BuiltinFunction::Floor),
+    unary("ceil", BuiltinFunction::Ceil),
+    unary("round", BuiltinFunction::Round),
+    Builtin {
+        name: "log",
+        arity: Arity::range(1, 2),
+        function: BuiltinFunction::Float(|arguments| {
+            arguments[0].log(*arguments.get(1).unwrap_or(&10.0))
+        }),
+    },
+    Builtin {
+        name: "atan2",
+        arity: Arity::exact(2),
+        function: BuiltinFunction::Angle(|arguments| arguments[0].atan2(arguments[1])),
+    },
+    Builtin {
+        name: "min",
+        arity: Arity::at_least(1),
+        function: BuiltinFunction::Min,
+    },
+    Builtin {
+        name: "max",
+        arity: Arity::at_least(1),
+        function: BuiltinFunction::Max,
+    },
+];
+
+#[cfg(test)]
+mod function_tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use crate::{
+        expression::ExpressionKind, expression_builder::ExpressionBuilder, number::NumberError,
+        operator::Operator,
+    };
+
+    #[test]
+    fn arity() {
+        assert!(Arity::exact(1).accepts(1));
+        assert!(!Arity::exact(1).accepts(2));
+        assert!(Arity::range(1, 2).accepts(2));
+        assert!(!Arity::range(1, 2).accepts(0));
+        assert!(Arity::at_least(1).accepts(100));
+        assert!(!Arity::at_least(1).accepts(0));
+        assert_eq!(Arity::exact(1).to_string(), "1 argument");
+        assert_eq!(Arity::range(1, 2).to_string(), "1 to 2 arguments");
+        assert_eq!(Arity::at_least(2).to_string(), "at least 2 arguments");
+    }
+
+    #[test]
+    fn builtin() {
+        assert!(Builtin::get("sqrt").is_some_and(|sqrt| sqrt.call(&[4.0], Span::new(0, 1), AngleUnit::Radians) == Ok(2.0)));
+        assert!(Builtin::get("log").is_some_and(|log| log.call(&[100.0], Span::new(0, 1), AngleUnit::Radians) == Ok(2.0)));
+        assert!(Builtin::get("log").is_some_and(|log| log.call(&[8.0, 2.0], Span::new(0, 1), AngleUnit::Radians) == Ok(3.0)));
+        assert!(Builtin::get("min").is_some_and(|min| min.call(&[3.0, -1.0, 2.0], Span::new(0, 1), AngleUnit::Radians) == Ok(-1.0)));
+        assert!(Builtin::get("max").is_some_and(|max| max.call(&[3.0, -1.0, 2.0], Span::new(0, 1), AngleUnit::Radians) == Ok(3.0)));
+        assert!(Builtin::get("floor").is_some_and(|floor| floor.call(&[-0.5f32], Span::new(0, 1), AngleUnit::Radians) == Ok(-1.0)));
+        assert!(Builtin::get("foo").is_none());
+        assert!(Builtin::get("sin").is_some_and(|sin| sin.call(&[90.0], Span::new(0, 1), AngleUnit::Degrees) == Ok(1.0)));
+        assert!(Builtin::get("acos").is_some_and(|acos| acos.call(&[-1.0], Span::new(0, 1), AngleUnit::Gradians) == Ok(200.0)));
+        assert!(Builtin::get("atan2").is_some_and(|atan2| atan2.call(&[1.0, 0.0], Span::new(0, 1), AngleUnit::Degrees) == Ok(90.0)));
+        assert!(Builtin::get("atan2").is_some_and(|atan2| atan2.call::<f64>(&[], Span::new(0, 1), AngleUnit::Radians) == Err(EvalError::WrongArgumentCount {
+            function: "atan2".to_string(),
+            expected: Arity::exact(2),
+            found: 0,
+            span: Span::new(0, 1),
+        })));
+    }
+
+    #[test]
+    fn builtin_exact() {
+        let decimal = |number: &str| number.parse::<BigDecimal>().unwrap();
+        assert!(Builtin::get("max").is_some_and(|max| max
+            .call(&[decimal("0.1"), decimal("0.3"), decimal("0.2")], Span::new(0, 1), AngleUnit::Radians)
+            == Ok(decimal("0.3"))));
+        assert!(Builtin::get("abs").is_some_and(|abs| abs.call(&[decimal("-0.1")], Span::new(0, 1), AngleUnit::Radians) == Ok(decimal("0.1"))));
+        assert!(Builtin::get("sqrt").is_some_and(|sqrt| sqrt.call(&[decimal("4")], Span::new(0, 1), AngleUnit::Radians) == Ok(decimal("2"))));
+        assert!(Builtin::get("cbrt").is_some_and(|cbrt| cbrt.call(&[decimal("2")], Span::new(0, 1), AngleUnit::Radians) == Err(EvalError::NumberError(NumberError::Inexact, Span::new(0, 1)))));
+        assert!(Builtin::get("sin").is_some_and(|sin| sin.call(&[decimal("0")], Span::new(0, 1), AngleUnit::Radians) == Err(EvalError::FloatFunction("sin".to_string(), Span::new(0, 1)))));
+    }
+
+    #[test]
+    fn user_function() {
+        let function = UserFunction::new(
+            vec!["x".to_string()],
+            ExpressionBuilder::new("x*x").get_expression().unwrap(),
+        );
+        assert_eq!(function.arity(), Arity::exact(1));
+        assert_eq!(
+            function.apply(&[3.0]),
+            Expression::binary(
+                Expression::new(ExpressionKind::Literal(3.0), Span::new(0, 1)),
+                Operator::Multiply,
+                Span::new(1, 2),
+                Expression::new(ExpressionKind::Literal(3.0), Span::new(2, 3)),
+            )
+        );
+    }
+}
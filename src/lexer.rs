@@ -0,0 +1,390 @@
+use std::{iter::Peekable, str::CharIndices};
+
+use bigdecimal::num_bigint::BigInt;
+
+use crate::{
+    operator::{Operator, UnaryOperator},
+    span::Span,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TokenKind {
+    /// A numeric literal, normalized to decimal digits so `0x1F` holds `31` and `1_000` holds `1000`.
+    Number(String),
+    Identifier(String),
+    Operator(Operator),
+    /// `~`, which only ever applies to a single operand.
+    UnaryOperator(UnaryOperator),
+    /// `!`, a factorial after an operand and negating a condition before one.
+    Exclamation,
+    Question,
+    Colon,
+    LeftParenthesis,
+    RightParenthesis,
+    Comma,
+    Equals,
+    /// Separates statements, as in `x = 2; x^2`.
+    Semicolon,
+    Unknown(char),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte offsets into the source.
+    pub span: Span,
+}
+
+/// Splits the source into tokens, skipping whitespace between them.
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            chars: source.char_indices().peekable(),
+        }
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map_or(self.source.len(), |(position, _)| *position)
+    }
+
+    fn next_if(&mut self, func: impl FnOnce(&char) -> bool) -> Option<char> {
+        self.chars
+            .next_if(|(_, character)| func(character))
+            .map(|(_, character)| character)
+    }
+
+    fn peek_is(&mut self, func: impl FnOnce(&char) -> bool) -> bool {
+        self.chars.peek().is_some_and(|(_, character)| func(character))
+    }
+
+    /// Reads digits in `radix`, dropping the `_` separating digit groups as in `1_000_000`.
+    fn get_digits(&mut self, radix: u32) -> String {
+        let mut digits = String::new();
+        loop {
+            if let Some(digit) = self.next_if(|character| character.is_digit(radix)) {
+                digits.push(digit);
+                continue;
+            }
+            let mut lookahead = self.clone();
+            if digits.is_empty()
+                || lookahead.next_if(|character| character == &'_').is_none()
+                || !lookahead.peek_is(|character| character.is_digit(radix))
+            {
+                return digits;
+            }
+            *self = lookahead;
+        }
+    }
+
+    /// Reads a `0x`, `0b` or `0o` prefix, but only if a digit of that radix follows it, so `0x`
+    /// alone stays `0` times `x`.
+    fn get_radix(&mut self) -> Option<u32> {
+        let mut lookahead = self.clone();
+        lookahead.next_if(|character| character == &'0')?;
+        let radix = match lookahead.next_if(|_| true)?.to_ascii_lowercase() {
+            'x' => 16,
+            'b' => 2,
+            'o' => 8,
+            _ => return None,
+        };
+        if !lookahead.peek_is(|character| character.is_digit(radix)) {
+            return None;
+        }
+        *self = lookahead;
+        Some(radix)
+    }
+
+    /// Reads an exponent such as `e-9` or `E23`, leaving `2e` to be `2` times `e`.
+    fn get_exponent(&mut self) -> Option<String> {
+        let mut lookahead = self.clone();
+        let mut exponent =
+            String::from(lookahead.next_if(|character| matches!(character, 'e' | 'E'))?);
+        if let Some(sign) = lookahead.next_if(|character| matches!(character, '+' | '-')) {
+            exponent.push(sign);
+        }
+        let digits = lookahead.get_digits(10);
+        if digits.is_empty() {
+            return None;
+        }
+        exponent.push_str(&digits);
+        *self = lookahead;
+        Some(exponent)
+    }
+
+    /// Reads a numeric literal: decimal with an optional exponent, e.g. `6.02E23`, or a
+    /// hexadecimal, binary or octal integer, e.g. `0x1F`.
+    fn get_number(&mut self) -> Option<String> {
+        if !self.peek_is(|character| character.is_ascii_digit() || character == &'.') {
+            return None;
+        }
+        if let Some(radix) = self.get_radix() {
+            let digits = self.get_digits(radix);
+            return Some(
+                BigInt::parse_bytes(digits.as_bytes(), radix)
+                    .map_or(digits, |integer| integer.to_string()),
+            );
+        }
+        let mut number = self.get_digits(10);
+        if let Some(point) = self.next_if(|character| character == &'.') {
+            number.push(point);
+            number.push_str(&self.get_digits(10));
+        }
+        if let Some(exponent) = self.get_exponent() {
+            number.push_str(&exponent);
+        }
+        Some(number)
+    }
+
+    fn get_identifier(&mut self) -> Option<String> {
+        let mut identifier = String::new();
+        while let Some(character) = self.next_if(|character| {
+            character.is_ascii_alphabetic()
+                || character == &'_'
+                || (character.is_ascii_digit() && !identifier.is_empty())
+        }) {
+            identifier.push(character);
+        }
+        (!identifier.is_empty()).then_some(identifier)
+    }
+
+    /// Reads an operator, preferring the two character `//`, `<<` and `>>` over a single one.
+    fn get_operator(&mut self) -> Option<Operator> {
+        let start = self.position();
+        for length in [2, 1] {
+            let Some(operator) = self
+                .source
+                .get(start..start + length)
+                .and_then(|operator| operator.parse().ok())
+            else {
+                continue;
+            };
+            // Operators are ASCII, so every character is a single byte.
+            for _ in 0..length {
+                self.chars.next();
+            }
+            return Some(operator);
+        }
+        None
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .next_if(|character| character.is_ascii_whitespace())
+            .is_some()
+        {}
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.skip_whitespace();
+        let start = self.position();
+        let kind = if let Some(number) = self.get_number() {
+            TokenKind::Number(number)
+        } else if let Some(identifier) = self.get_identifier() {
+            // Keyword operators such as `xor` read like names.
+            identifier
+                .parse()
+                .map_or(TokenKind::Identifier(identifier), TokenKind::Operator)
+        } else if let Some(operator) = self.get_operator() {
+            TokenKind::Operator(operator)
+        } else {
+            match self.next_if(|_| true)? {
+                '(' => TokenKind::LeftParenthesis,
+                ')' => TokenKind::RightParenthesis,
+                ',' => TokenKind::Comma,
+                '=' => TokenKind::Equals,
+                ';' => TokenKind::Semicolon,
+                '!' => TokenKind::Exclamation,
+                '?' => TokenKind::Question,
+                ':' => TokenKind::Colon,
+                character => UnaryOperator::new(&character)
+                    .map_or(TokenKind::Unknown(character), TokenKind::UnaryOperator),
+            }
+        };
+        Some(Token {
+            kind,
+            span: Span::new(start, self.position()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod lexer_tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        Lexer::new(source).map(|token| token.kind).collect()
+    }
+
+    fn number(number: &str) -> TokenKind {
+        TokenKind::Number(number.to_string())
+    }
+
+    fn identifier(identifier: &str) -> TokenKind {
+        TokenKind::Identifier(identifier.to_string())
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            kinds("f(x, 2) = x^2"),
+            vec![
+                identifier("f"),
+                TokenKind::LeftParenthesis,
+                identifier("x"),
+                TokenKind::Comma,
+                number("2"),
+                TokenKind::RightParenthesis,
+                TokenKind::Equals,
+                identifier("x"),
+                TokenKind::Operator(Operator::Power),
+                number("2"),
+            ]
+        );
+        for operator in ['+', '-', '*', '/', '^', '%'] {
+            assert_eq!(
+                kinds(&operator.to_string()),
+                vec![TokenKind::Operator(Operator::new(&operator).unwrap())]
+            );
+        }
+        for operator in ["&", "|", "//", "<<", ">>", "xor", "<", "<=", "==", "!=", ">", ">=", "&&", "||"] {
+            assert_eq!(
+                kinds(&format!("x {} y", operator)),
+                vec![
+                    identifier("x"),
+                    TokenKind::Operator(operator.parse().unwrap()),
+                    identifier("y")
+                ]
+            );
+        }
+        assert_eq!(
+            kinds("~3!"),
+            vec![
+                TokenKind::UnaryOperator(UnaryOperator::BitNot),
+                number("3"),
+                TokenKind::Exclamation
+            ]
+        );
+        assert_eq!(
+            kinds("x<=-1!=!y"),
+            vec![
+                identifier("x"),
+                TokenKind::Operator(Operator::LessEqual),
+                TokenKind::Operator(Operator::Subtract),
+                number("1"),
+                TokenKind::Operator(Operator::NotEqual),
+                TokenKind::Exclamation,
+                identifier("y")
+            ]
+        );
+        assert_eq!(
+            kinds("x=-1"),
+            vec![identifier("x"), TokenKind::Equals, TokenKind::Operator(Operator::Subtract), number("1")]
+        );
+        assert_eq!(
+            kinds("1/ /2"),
+            vec![
+                number("1"),
+                TokenKind::Operator(Operator::Divide),
+                TokenKind::Operator(Operator::Divide),
+                number("2")
+            ]
+        );
+        assert_eq!(kinds("xor1"), vec![identifier("xor1")]);
+        assert_eq!(
+            kinds("x?1:2"),
+            vec![
+                identifier("x"),
+                TokenKind::Question,
+                number("1"),
+                TokenKind::Colon,
+                number("2")
+            ]
+        );
+        assert_eq!(kinds("1;x"), vec![number("1"), TokenKind::Semicolon, identifier("x")]);
+        assert_eq!(kinds("1$"), vec![number("1"), TokenKind::Unknown('$')]);
+        assert_eq!(kinds(" \t\n"), vec![]);
+    }
+
+    #[test]
+    fn spans() {
+        let spans = Lexer::new(" max(12, x_1)")
+            .map(|token| token.span)
+            .collect::<Vec<Span>>();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(1, 4),
+                Span::new(4, 5),
+                Span::new(5, 7),
+                Span::new(7, 8),
+                Span::new(9, 12),
+                Span::new(12, 13),
+            ]
+        );
+        assert_eq!(
+            Lexer::new("é+1").map(|token| token.span).collect::<Vec<Span>>(),
+            vec![Span::new(0, 2), Span::new(2, 3), Span::new(3, 4)]
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        for whole in 0..100u16 {
+            for rest in 0..100 {
+                let literal = format!("{}.{}", whole, rest);
+                assert_eq!(kinds(&literal), vec![number(&literal)]);
+            }
+        }
+        for (literal, normalized) in [
+            (".0", ".0"),
+            ("0.", "0."),
+            (".", "."),
+            ("1e-9", "1e-9"),
+            ("6.02E23", "6.02E23"),
+            ("2.5e+3", "2.5e+3"),
+            ("0x1F", "31"),
+            ("0xff", "255"),
+            ("0b1010", "10"),
+            ("0o17", "15"),
+            ("0x10000000000000000", "18446744073709551616"),
+            ("1_000_000", "1000000"),
+            ("1_000.000_5", "1000.0005"),
+        ] {
+            assert_eq!(kinds(literal), vec![number(normalized)], "{}", literal);
+        }
+        assert_eq!(kinds("2e"), vec![number("2"), identifier("e")]);
+        assert_eq!(kinds("2e3x"), vec![number("2e3"), identifier("x")]);
+        assert_eq!(kinds("0x"), vec![number("0"), identifier("x")]);
+        assert_eq!(kinds("0b2"), vec![number("0"), identifier("b2")]);
+        assert_eq!(kinds("1_x"), vec![number("1"), identifier("_x")]);
+        assert_eq!(
+            kinds("1.2.3"),
+            vec![number("1.2"), number(".3")]
+        );
+    }
+
+    #[test]
+    fn identifiers() {
+        for name in ["x", "_x", "x_1", "rate"] {
+            assert_eq!(kinds(name), vec![identifier(name)]);
+        }
+        assert_eq!(kinds("1x"), vec![number("1"), identifier("x")]);
+        assert_eq!(
+            kinds("x+1"),
+            vec![identifier("x"), TokenKind::Operator(Operator::Add), number("1")]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! The calculator's engine: parses statements such as `x = 2` and `f(x) = x^2`, and evaluates
+//! them with floats, exact decimals or exact fractions.
+//!
+//! ```
+//! use console_calculator::{evaluate, EvalContext, Value};
+//!
+//! let mut context = EvalContext::<f64>::new();
+//! assert_eq!(evaluate("f(x) = x^2; f(3) + 1", &mut context), Ok(Some(Value::Number(10.0))));
+//! assert_eq!(context.get("pi"), Some(std::f64::consts::PI));
+//! ```
+//!
+//! The context also configures evaluation, e.g. the unit of angles:
+//!
+//! ```
+//! use console_calculator::{evaluate, AngleUnit, EvalContext, Value};
+//!
+//! let mut context = EvalContext::<f64>::new();
+//! context.set_angle_unit(AngleUnit::Degrees);
+//! context.set_max_call_depth(16);
+//! assert_eq!(evaluate("sin(90)", &mut context), Ok(Some(Value::Number(1.0))));
+//! ```
+//!
+//! Everything exported here is the stable interface, the modules behind it may change.
+
+mod angle;
+mod context;
+mod decimal;
+mod eval;
+mod expression;
+mod expression_builder;
+mod format;
+mod function;
+mod lexer;
+mod number;
+mod operator;
+mod rational;
+mod span;
+mod statement;
+mod value;
+
+pub use angle::{AngleUnit, AngleUnitError};
+pub use context::{EvalContext, FloatPolicy, MAX_CALL_DEPTH, MAX_DEPTH};
+pub use eval::{EvalError, Evaluate};
+pub use expression_builder::ExpressionBuilderError;
+pub use format::{OutputFormat, OutputFormatError};
+pub use function::Arity;
+pub use number::{Number, NumberError};
+pub use rational::Rational;
+pub use span::Span;
+pub use statement::Statement;
+pub use value::Value;
+
+// The fuzz targets drive the builder directly, it isn't part of the stable interface.
+#[doc(hidden)]
+pub use expression_builder::ExpressionBuilder;
+
+/// Parses `;`-separated statements, returning every syntax error in the source if any. A variable
+/// the source assigns is multiplied by a parenthesized operand following it, as in `x = 3; x(2)`,
+/// any other name followed by `(` being a call.
+pub fn parse<N: Number>(source: &str) -> Result<Vec<Statement<N>>, Vec<ExpressionBuilderError>> {
+    ExpressionBuilder::new(source).get_statements()
+}
+
+/// Parses like `parse`, also reading the variables of `context` as variables, so `x(2)` is `x*(2)`
+/// when `context` has a variable `x`.
+pub fn parse_in<N: Number>(
+    source: &str,
+    context: &EvalContext<N>,
+) -> Result<Vec<Statement<N>>, Vec<ExpressionBuilderError>> {
+    ExpressionBuilder::new(source).with_context(context).get_statements()
+}
+
+/// Evaluates `;`-separated statements in order, returning the value of the last one. A function
+/// definition has no value.
+///
+/// Evaluation recurses, see [`MAX_DEPTH`] for the stack it needs.
+pub fn evaluate<N: Number>(
+    source: &str,
+    context: &mut EvalContext<N>,
+) -> Result<Option<Value<N>>, EvalError> {
+    source.eval(context)
+}
@@ -1,29 +1,63 @@
-use clap::{Command, arg, ArgMatches};
+use std::path::PathBuf;
 
-use crate::eval::Evaluate;
+use bigdecimal::BigDecimal;
+use clap::{Command, arg, ArgMatches, value_parser};
+
+use console_calculator::{AngleUnit, EvalContext, FloatPolicy, Number, OutputFormat, Rational};
 const VERSION: &str = "1.0.0";
 
-pub mod operator;
-pub mod expression_item;
-pub mod eval;
-pub mod expression_builder;
+mod repl;
 
 fn get_cmd() -> ArgMatches {
     Command::new("calc").about("Calculator").args(&[
         arg!(-v --version "Prints the version number"),
-        arg!(-e --equation <String> "Equation")
+        arg!(-e --equation <String> "Equation"),
+        arg!(-f --file <PATH> "Evaluates a file line by line, sharing variables and functions")
+            .value_parser(value_parser!(PathBuf)),
+        arg!(-r --repl "Starts an interactive session (default when no equation is given)"),
+        arg!(-d --decimal "Uses exact arbitrary precision decimals instead of floating point"),
+        arg!(--rational "Uses exact fractions instead of floating point").conflicts_with("decimal"),
+        arg!(--format <FORMAT> "Writes results as decimal, scientific, hex, binary or octal")
+            .value_parser(value_parser!(OutputFormat)),
+        arg!(--strict "Reports division by zero, overflow and NaN as errors instead of inf and NaN"),
+        arg!(--angle <UNIT> "Measures the angles of trigonometric functions in deg, rad or grad")
+            .value_parser(value_parser!(AngleUnit)),
     ]).get_matches()
 } 
 
+fn run<N: Number>(matches: &ArgMatches, version: bool) {
+    let format = matches.get_one::<OutputFormat>("format").copied().unwrap_or_default();
+    let mut context = EvalContext::<N>::new();
+    if matches.get_one::<bool>("strict").is_some_and(|bool| *bool) {
+        context.set_policy(FloatPolicy::Strict);
+    }
+    if let Some(angle_unit) = matches.get_one::<AngleUnit>("angle") {
+        context.set_angle_unit(*angle_unit);
+    }
+    if let Some(equation) = matches.get_one::<String>("equation") {
+        repl::run_equation(equation, &mut context, format);
+    } else if let Some(path) = matches.get_one::<PathBuf>("file") {
+        if let Err(err) = repl::run_file(path, context, format) {
+            println!("{}", err);
+        }
+    } else if matches.get_one::<bool>("repl").is_some_and(|bool| *bool) || !version {
+        if let Err(err) = repl::run(context, format) {
+            println!("{}", err);
+        }
+    }
+}
+
 fn main() {
     let matches = get_cmd();
-    if matches.get_one::<bool>("version").is_some_and(|bool| *bool) {
+    let version = matches.get_one::<bool>("version").is_some_and(|bool| *bool);
+    if version {
         println!("{}", VERSION);
     };
-    if let Some(equation) = matches.get_one::<String>("equation") {
-        match equation.as_str().eval() {
-            Ok(result) => println!("{}", result.unwrap()),
-            Err(err) => println!("{}", err)
-        }
+    if matches.get_one::<bool>("decimal").is_some_and(|bool| *bool) {
+        run::<BigDecimal>(&matches, version);
+    } else if matches.get_one::<bool>("rational").is_some_and(|bool| *bool) {
+        run::<Rational>(&matches, version);
+    } else {
+        run::<f64>(&matches, version);
     }
 }
@@ -0,0 +1,314 @@
+use bigdecimal::{num_bigint::BigInt, ToPrimitive};
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+/// Numeric type the calculator evaluates with. The arithmetic mirrors the `Operator` variants and
+/// fails when a backend can't represent the result.
+pub trait Number: Clone + Debug + Display + PartialEq + PartialOrd + FromStr {
+    /// Whether every result is exact, functions only computed in floating point, such as `sin`,
+    /// aren't available then.
+    const EXACT: bool = false;
+
+    fn add(&self, other: &Self) -> Result<Self, NumberError>;
+    fn subtract(&self, other: &Self) -> Result<Self, NumberError>;
+    fn multiply(&self, other: &Self) -> Result<Self, NumberError>;
+    fn divide(&self, other: &Self) -> Result<Self, NumberError>;
+    fn remainder(&self, other: &Self) -> Result<Self, NumberError>;
+    fn power(&self, exponent: &Self) -> Result<Self, NumberError>;
+    fn negate(&self) -> Self;
+    fn abs(&self) -> Self;
+    fn floor(&self) -> Self;
+    fn ceil(&self) -> Self;
+    fn round(&self) -> Self;
+    /// Converts the result of a floating point computation, exact backends refuse since the
+    /// value is only an approximation.
+    fn from_f64(value: f64) -> Result<Self, NumberError>;
+    fn to_f64(&self) -> f64;
+    /// The exact integer value, `None` if the number has a fractional part.
+    fn to_integer(&self) -> Option<BigInt>;
+    fn from_integer(integer: BigInt) -> Result<Self, NumberError>;
+    fn is_zero(&self) -> bool;
+
+    /// Writes the number as a mantissa and an exponent, as in `6.02e23`.
+    fn to_scientific(&self) -> String {
+        format!("{:e}", self.to_f64())
+    }
+
+    /// The `degree`th root, `2` for a square root. Exact backends fail unless the root is exact.
+    fn root(&self, degree: u32) -> Result<Self, NumberError> {
+        let value = self.to_f64();
+        Self::from_f64(match degree {
+            2 => value.sqrt(),
+            3 => value.cbrt(),
+            _ => value.powf(1.0 / f64::from(degree)),
+        })
+    }
+
+    /// Only floats can be NaN, exact backends fail instead.
+    fn is_nan(&self) -> bool {
+        false
+    }
+
+    fn is_infinite(&self) -> bool {
+        false
+    }
+
+    /// Division rounded towards negative infinity, so `-7//2` is `-4`.
+    fn floor_divide(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(self.divide(other)?.floor())
+    }
+
+    fn bit_and(&self, other: &Self) -> Result<Self, NumberError> {
+        Self::from_integer(integer(self)? & integer(other)?)
+    }
+
+    fn bit_or(&self, other: &Self) -> Result<Self, NumberError> {
+        Self::from_integer(integer(self)? | integer(other)?)
+    }
+
+    fn bit_xor(&self, other: &Self) -> Result<Self, NumberError> {
+        Self::from_integer(integer(self)? ^ integer(other)?)
+    }
+
+    /// Flips every bit of the two's complement representation, so `~x` is `-x-1`.
+    fn bit_not(&self) -> Result<Self, NumberError> {
+        Self::from_integer(!integer(self)?)
+    }
+
+    fn shift_left(&self, shift: &Self) -> Result<Self, NumberError> {
+        Self::from_integer(integer(self)? << shift_amount(shift)?)
+    }
+
+    /// Rounds towards negative infinity, like `floor_divide` by a power of two.
+    fn shift_right(&self, shift: &Self) -> Result<Self, NumberError> {
+        Self::from_integer(integer(self)? >> shift_amount(shift)?)
+    }
+
+    fn factorial(&self) -> Result<Self, NumberError> {
+        let number = integer(self)?
+            .to_u32()
+            .filter(|number| *number <= MAX_FACTORIAL)
+            .ok_or(NumberError::OutOfRange)?;
+        Self::from_integer((1..=number).map(BigInt::from).product())
+    }
+}
+
+/// Largest shift accepted by `<<` and `>>`, keeping the integers they build to a sensible size.
+pub const MAX_SHIFT: usize = 1 << 16;
+/// Largest operand accepted by `!`.
+pub const MAX_FACTORIAL: u32 = 10_000;
+/// Largest number of digits, roughly, of an exact power, `9^9^9` would otherwise take hours.
+pub const MAX_POWER_DIGITS: u64 = 1 << 20;
+/// Largest exponent of a literal such as `1e999`, exact backends store it with as many digits.
+pub const MAX_EXPONENT: u64 = 1 << 16;
+
+fn integer<N: Number>(number: &N) -> Result<BigInt, NumberError> {
+    number.to_integer().ok_or(NumberError::NotAnInteger)
+}
+
+fn shift_amount<N: Number>(shift: &N) -> Result<usize, NumberError> {
+    integer(shift)?
+        .to_usize()
+        .filter(|shift| *shift <= MAX_SHIFT)
+        .ok_or(NumberError::OutOfRange)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NumberError {
+    DivisionByZero,
+    Inexact,
+    /// An integer-only operation, such as `&` or `!`, was applied to a fraction.
+    NotAnInteger,
+    /// A negative or overly large shift or factorial operand, or a negative even root.
+    OutOfRange,
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberError::DivisionByZero => write!(f, "Division by zero"),
+            NumberError::Inexact => write!(f, "Result cannot be represented exactly"),
+            NumberError::NotAnInteger => write!(f, "Operand must be an integer"),
+            NumberError::OutOfRange => write!(f, "Operand out of range"),
+        }
+    }
+}
+
+impl Error for NumberError {}
+
+macro_rules! impl_float_number {
+    ($float:ty) => {
+        impl Number for $float {
+            fn add(&self, other: &Self) -> Result<Self, NumberError> {
+                Ok(self + other)
+            }
+
+            fn subtract(&self, other: &Self) -> Result<Self, NumberError> {
+                Ok(self - other)
+            }
+
+            fn multiply(&self, other: &Self) -> Result<Self, NumberError> {
+                Ok(self * other)
+            }
+
+            fn divide(&self, other: &Self) -> Result<Self, NumberError> {
+                Ok(self / other)
+            }
+
+            fn remainder(&self, other: &Self) -> Result<Self, NumberError> {
+                Ok(self % other)
+            }
+
+            fn power(&self, exponent: &Self) -> Result<Self, NumberError> {
+                Ok(self.powf(*exponent))
+            }
+
+            fn negate(&self) -> Self {
+                -*self
+            }
+
+            fn abs(&self) -> Self {
+                <$float>::abs(*self)
+            }
+
+            fn floor(&self) -> Self {
+                <$float>::floor(*self)
+            }
+
+            fn ceil(&self) -> Self {
+                <$float>::ceil(*self)
+            }
+
+            fn round(&self) -> Self {
+                <$float>::round(*self)
+            }
+
+            fn from_f64(value: f64) -> Result<Self, NumberError> {
+                Ok(value as $float)
+            }
+
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+
+            fn to_integer(&self) -> Option<BigInt> {
+                if self.fract() != 0.0 {
+                    return None;
+                }
+                bigdecimal::FromPrimitive::from_f64(*self as f64)
+            }
+
+            fn from_integer(integer: BigInt) -> Result<Self, NumberError> {
+                // Too large integers become infinite, like any other float overflow.
+                Ok(integer.to_f64().unwrap_or(f64::NAN) as $float)
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0.0
+            }
+
+            fn is_nan(&self) -> bool {
+                <$float>::is_nan(*self)
+            }
+
+            fn is_infinite(&self) -> bool {
+                <$float>::is_infinite(*self)
+            }
+        }
+    };
+}
+
+impl_float_number!(f32);
+impl_float_number!(f64);
+
+#[cfg(test)]
+mod number_tests {
+    use super::*;
+    use crate::rational::Rational;
+    use bigdecimal::BigDecimal;
+
+    fn arithmetic<N: Number>() {
+        let number = |number: &str| number.parse::<N>().ok().unwrap();
+        let (two, three) = (number("2"), number("3"));
+        assert_eq!(two.add(&three), Ok(number("5")));
+        assert_eq!(two.subtract(&three), Ok(number("-1")));
+        assert_eq!(two.multiply(&three), Ok(number("6")));
+        assert_eq!(three.divide(&two), Ok(number("1.5")));
+        assert_eq!(three.remainder(&two), Ok(number("1")));
+        assert_eq!(two.power(&three), Ok(number("8")));
+        assert_eq!(two.negate(), number("-2"));
+        assert_eq!(number("-1.5").abs(), number("1.5"));
+        assert_eq!(number("-1.5").floor(), number("-2"));
+        assert_eq!(number("-1.5").ceil(), number("-1"));
+        assert_eq!(number("2.5").round(), number("3"));
+        assert_eq!(three.to_f64(), 3.0);
+        assert_eq!(three.to_integer(), Some(BigInt::from(3)));
+        assert_eq!(number("-1.5").to_integer(), None);
+        assert_eq!(N::from_integer(BigInt::from(-3)), Ok(number("-3")));
+        assert!(number("-0").is_zero());
+        assert!(!two.is_zero());
+        assert!(!two.is_nan() && !two.is_infinite());
+    }
+
+    fn integer_arithmetic<N: Number>() {
+        let number = |number: &str| number.parse::<N>().ok().unwrap();
+        let (six, three) = (number("6"), number("3"));
+        assert_eq!(number("7").floor_divide(&number("2")), Ok(number("3")));
+        assert_eq!(number("-7").floor_divide(&number("2")), Ok(number("-4")));
+        assert_eq!(number("7.5").floor_divide(&number("-2")), Ok(number("-4")));
+        assert_eq!(six.bit_and(&three), Ok(number("2")));
+        assert_eq!(six.bit_or(&three), Ok(number("7")));
+        assert_eq!(six.bit_xor(&three), Ok(number("5")));
+        assert_eq!(six.bit_not(), Ok(number("-7")));
+        assert_eq!(six.shift_left(&three), Ok(number("48")));
+        assert_eq!(six.shift_right(&number("1")), Ok(three.clone()));
+        assert_eq!(number("-7").shift_right(&number("1")), Ok(number("-4")));
+        assert_eq!(three.factorial(), Ok(six.clone()));
+        assert_eq!(number("0").factorial(), Ok(number("1")));
+        assert_eq!(number("1.5").bit_and(&three), Err(NumberError::NotAnInteger));
+        assert_eq!(three.shift_left(&number("0.5")), Err(NumberError::NotAnInteger));
+        assert_eq!(number("2.5").factorial(), Err(NumberError::NotAnInteger));
+        assert_eq!(three.shift_left(&number("-1")), Err(NumberError::OutOfRange));
+        assert_eq!(number("-1").factorial(), Err(NumberError::OutOfRange));
+        assert_eq!(number("100000").factorial(), Err(NumberError::OutOfRange));
+    }
+
+    #[test]
+    fn f32_arithmetic() {
+        arithmetic::<f32>();
+    }
+
+    #[test]
+    fn f64_arithmetic() {
+        arithmetic::<f64>();
+    }
+
+    #[test]
+    fn integer_operations() {
+        integer_arithmetic::<f32>();
+        integer_arithmetic::<f64>();
+        integer_arithmetic::<BigDecimal>();
+        integer_arithmetic::<Rational>();
+    }
+
+    #[test]
+    fn precision() {
+        assert_eq!(16_777_217f64.add(&1.0), Ok(16_777_218.0));
+        assert_ne!(16_777_217f32.add(&1.0), Ok(16_777_218.0));
+    }
+
+    #[test]
+    fn special_values() {
+        assert!(f64::NAN.is_nan() && !f64::NAN.is_infinite());
+        assert!(Number::is_infinite(&f32::NEG_INFINITY));
+    }
+
+    #[test]
+    fn from_f64() {
+        assert_eq!(f64::from_f64(0.1), Ok(0.1));
+        assert_eq!(f32::from_f64(0.1), Ok(0.1));
+    }
+}
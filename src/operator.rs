@@ -1,5 +1,6 @@
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, str::FromStr};
 
+use crate::angle::AngleUnit;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Operator {
@@ -9,6 +10,20 @@ pub enum Operator {
     Divide,
     Power,
     Remainder,
+    FloorDivide,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
 }
 
 impl Operator {
@@ -20,9 +35,40 @@ impl Operator {
             '/' => Ok(Operator::Divide),
             '^' => Ok(Operator::Power),
             '%' => Ok(Operator::Remainder),
+            '&' => Ok(Operator::BitAnd),
+            '|' => Ok(Operator::BitOr),
+            '<' => Ok(Operator::Less),
+            '>' => Ok(Operator::Greater),
             _ => Err(OperatorError::UnexpectedOperator),
         }
     }
+
+    /// Binding strength, operators with a higher precedence are applied first. From loosest to
+    /// tightest: `||`, `&&`, comparisons, `|`, `xor`, `&`, shifts, `+ -`, `* / // %` and `^`.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Less
+            | Operator::LessEqual
+            | Operator::Equal
+            | Operator::NotEqual
+            | Operator::Greater
+            | Operator::GreaterEqual => 3,
+            Operator::BitOr => 4,
+            Operator::BitXor => 5,
+            Operator::BitAnd => 6,
+            Operator::ShiftLeft | Operator::ShiftRight => 7,
+            Operator::Add | Operator::Subtract => 8,
+            Operator::Multiply | Operator::Divide | Operator::FloorDivide | Operator::Remainder => 9,
+            Operator::Power => 10,
+        }
+    }
+
+    /// Whether a chain like `2^3^2` groups from the right, as `2^(3^2)`.
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, Operator::Power)
+    }
 }
 
 impl TryFrom<&char> for Operator {
@@ -33,15 +79,29 @@ impl TryFrom<&char> for Operator {
     }
 }
 
-impl From<&Operator> for char {
-    fn from(operator: &Operator) -> Self {
+/// Also accepts the operators spelled with more than one character, such as `//`, `<=` and `xor`.
+impl FromStr for Operator {
+    type Err = OperatorError;
+
+    fn from_str(operator: &str) -> Result<Self, Self::Err> {
         match operator {
-            Operator::Add => '+',
-            Operator::Subtract => '-',
-            Operator::Multiply => '*',
-            Operator::Divide => '/',
-            Operator::Power => '^',
-            Operator::Remainder => '%',
+            "//" => Ok(Operator::FloorDivide),
+            "<<" => Ok(Operator::ShiftLeft),
+            ">>" => Ok(Operator::ShiftRight),
+            "xor" => Ok(Operator::BitXor),
+            "<=" => Ok(Operator::LessEqual),
+            "==" => Ok(Operator::Equal),
+            "!=" => Ok(Operator::NotEqual),
+            ">=" => Ok(Operator::GreaterEqual),
+            "&&" => Ok(Operator::And),
+            "||" => Ok(Operator::Or),
+            _ => {
+                let mut chars = operator.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(operator), None) => Operator::new(&operator),
+                    _ => Err(OperatorError::UnexpectedOperator),
+                }
+            }
         }
     }
 }
@@ -55,10 +115,74 @@ impl Display for Operator {
             Operator::Divide => write!(f, "/"),
             Operator::Power => write!(f, "^"),
             Operator::Remainder => write!(f, "%"),
+            Operator::FloorDivide => write!(f, "//"),
+            Operator::BitAnd => write!(f, "&"),
+            Operator::BitOr => write!(f, "|"),
+            Operator::BitXor => write!(f, "xor"),
+            Operator::ShiftLeft => write!(f, "<<"),
+            Operator::ShiftRight => write!(f, ">>"),
+            Operator::Less => write!(f, "<"),
+            Operator::LessEqual => write!(f, "<="),
+            Operator::Equal => write!(f, "=="),
+            Operator::NotEqual => write!(f, "!="),
+            Operator::Greater => write!(f, ">"),
+            Operator::GreaterEqual => write!(f, ">="),
+            Operator::And => write!(f, "&&"),
+            Operator::Or => write!(f, "||"),
         }
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum UnaryOperator {
+    Plus,
+    Negate,
+    /// `~x`, flipping every bit of an integer, so `~x` is `-x-1`.
+    BitNot,
+    /// `x!`, written after its operand.
+    Factorial,
+    /// `!c`, negating a condition.
+    Not,
+    /// `90deg`, an angle in the given unit, written directly after its operand.
+    Angle(AngleUnit),
+}
+
+impl UnaryOperator {
+    pub fn new(operator: &char) -> Result<Self, OperatorError> {
+        match operator {
+            '+' => Ok(UnaryOperator::Plus),
+            '-' => Ok(UnaryOperator::Negate),
+            '~' => Ok(UnaryOperator::BitNot),
+            _ => Err(OperatorError::UnexpectedOperator),
+        }
+    }
+
+    pub fn is_postfix(&self) -> bool {
+        matches!(self, UnaryOperator::Factorial | UnaryOperator::Angle(_))
+    }
+
+    /// Lowest precedence of the binary operators a prefix takes in, so `-2^2` is `-(2^2)` while
+    /// `!x < 2` is `!(x<2)`. A factorial or unit binds tighter than any of them.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            UnaryOperator::Not => Operator::Less.precedence(),
+            UnaryOperator::Factorial | UnaryOperator::Angle(_) => Operator::Power.precedence() + 1,
+            _ => Operator::Power.precedence(),
+        }
+    }
+}
+
+impl Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOperator::Plus => write!(f, "+"),
+            UnaryOperator::Negate => write!(f, "-"),
+            UnaryOperator::BitNot => write!(f, "~"),
+            UnaryOperator::Factorial | UnaryOperator::Not => write!(f, "!"),
+            UnaryOperator::Angle(unit) => write!(f, "{}", unit),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum OperatorError {
@@ -81,7 +205,7 @@ mod operator_tests {
 
     #[test]
     fn operator() {
-        for operator in ['+', '-', '*', '/', '^', '%'] {
+        for operator in ['+', '-', '*', '/', '^', '%', '&', '|'] {
             assert!(Operator::new(&operator).is_ok());
         }
         assert!(Operator::new(&' ').is_err_and(|e| e == OperatorError::UnexpectedOperator));
@@ -96,11 +220,48 @@ mod operator_tests {
     }
 
     #[test]
-    fn into_char() {
-        for operator_char in &['+', '-', '*', '/', '^', '%'] {
-            let operator = Operator::try_from(operator_char);
-            assert!(&operator.is_ok());
-            assert_eq!(<char>::from(&operator.unwrap()), *operator_char);
+    fn from_str() {
+        for operator in [
+            "+", "-", "*", "/", "^", "%", "&", "|", "//", "<<", ">>", "xor", "<", "<=", "==", "!=",
+            ">", ">=", "&&", "||",
+        ] {
+            assert!(operator
+                .parse::<Operator>()
+                .is_ok_and(|ok| ok.to_string() == operator));
+        }
+        for operator in ["", "=", "!", "**", "+-", "and"] {
+            assert_eq!(operator.parse::<Operator>(), Err(OperatorError::UnexpectedOperator));
         }
     }
+
+    #[test]
+    fn precedence() {
+        assert!(Operator::Power.precedence() > Operator::Multiply.precedence());
+        assert_eq!(Operator::Multiply.precedence(), Operator::Remainder.precedence());
+        assert_eq!(Operator::Divide.precedence(), Operator::FloorDivide.precedence());
+        assert!(Operator::Divide.precedence() > Operator::Subtract.precedence());
+        assert!(Operator::Subtract.precedence() > Operator::ShiftLeft.precedence());
+        assert!(Operator::ShiftRight.precedence() > Operator::BitAnd.precedence());
+        assert!(Operator::BitAnd.precedence() > Operator::BitXor.precedence());
+        assert!(Operator::BitXor.precedence() > Operator::BitOr.precedence());
+        assert!(Operator::BitOr.precedence() > Operator::LessEqual.precedence());
+        assert_eq!(Operator::Less.precedence(), Operator::NotEqual.precedence());
+        assert!(Operator::Equal.precedence() > Operator::And.precedence());
+        assert!(Operator::And.precedence() > Operator::Or.precedence());
+        assert!(Operator::Power.is_right_associative());
+        assert!(!Operator::Subtract.is_right_associative());
+    }
+
+    #[test]
+    fn unary_operator() {
+        assert_eq!(UnaryOperator::new(&'-'), Ok(UnaryOperator::Negate));
+        assert_eq!(UnaryOperator::new(&'+'), Ok(UnaryOperator::Plus));
+        assert_eq!(UnaryOperator::new(&'~'), Ok(UnaryOperator::BitNot));
+        assert!(UnaryOperator::new(&'!').is_err_and(|e| e == OperatorError::UnexpectedOperator));
+        assert!(UnaryOperator::Factorial.is_postfix());
+        assert!(!UnaryOperator::Negate.is_postfix());
+        assert!(UnaryOperator::Angle(AngleUnit::Degrees).is_postfix());
+        assert_eq!(UnaryOperator::Angle(AngleUnit::Degrees).to_string(), "deg");
+        assert!(UnaryOperator::new(&'*').is_err_and(|e| e == OperatorError::UnexpectedOperator));
+    }
 }
@@ -0,0 +1,234 @@
+use std::{fmt::Display, str::FromStr};
+
+use bigdecimal::{
+    num_bigint::BigInt, BigDecimal, ParseBigDecimalError, Signed, ToPrimitive, Zero,
+};
+use num_rational::BigRational;
+
+use crate::number::{Number, NumberError, MAX_POWER_DIGITS};
+
+/// Exact fractions with a normalized big integer numerator and denominator, `1/3+1/6` is `1/2`.
+/// Operations without a rational result, e.g. `2^0.5`, fail with `NumberError::Inexact`.
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Rational(pub BigRational);
+
+impl Rational {
+    /// The normalized fraction `numerator/denominator`, failing if `denominator` is zero.
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Result<Self, NumberError> {
+        if denominator.is_zero() {
+            return Err(NumberError::DivisionByZero);
+        }
+        Ok(Self(BigRational::new(numerator, denominator)))
+    }
+}
+
+impl Number for Rational {
+    const EXACT: bool = true;
+
+    fn add(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(Self(&self.0 + &other.0))
+    }
+
+    fn subtract(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(Self(&self.0 - &other.0))
+    }
+
+    fn multiply(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(Self(&self.0 * &other.0))
+    }
+
+    fn divide(&self, other: &Self) -> Result<Self, NumberError> {
+        if other.0.is_zero() {
+            return Err(NumberError::DivisionByZero);
+        }
+        Ok(Self(&self.0 / &other.0))
+    }
+
+    fn remainder(&self, other: &Self) -> Result<Self, NumberError> {
+        if other.0.is_zero() {
+            return Err(NumberError::DivisionByZero);
+        }
+        Ok(Self(&self.0 % &other.0))
+    }
+
+    fn power(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if !exponent.0.is_integer() {
+            return Err(NumberError::Inexact);
+        }
+        let exponent = exponent.0.to_integer().to_i32().ok_or(NumberError::Inexact)?;
+        if self.0.is_zero() && exponent.is_negative() {
+            return Err(NumberError::DivisionByZero);
+        }
+        // A digit takes a little over three bits.
+        let digits = (self.0.numer().bits() + self.0.denom().bits()) / 3;
+        if digits.saturating_mul(exponent.unsigned_abs().into()) > MAX_POWER_DIGITS {
+            return Err(NumberError::OutOfRange);
+        }
+        Ok(Self(self.0.pow(exponent)))
+    }
+
+    fn negate(&self) -> Self {
+        Self(-&self.0)
+    }
+
+    fn abs(&self) -> Self {
+        Self(self.0.abs())
+    }
+
+    fn floor(&self) -> Self {
+        Self(self.0.floor())
+    }
+
+    fn ceil(&self) -> Self {
+        Self(self.0.ceil())
+    }
+
+    fn round(&self) -> Self {
+        Self(self.0.round())
+    }
+
+    /// Exact when both the numerator and the denominator have exact roots, `sqrt(4/9)` is `2/3`.
+    fn root(&self, degree: u32) -> Result<Self, NumberError> {
+        if self.0.is_negative() && degree.is_multiple_of(2) {
+            return Err(NumberError::OutOfRange);
+        }
+        let (numerator, denominator) = (self.0.numer(), self.0.denom());
+        let (numerator_root, denominator_root) =
+            (numerator.nth_root(degree), denominator.nth_root(degree));
+        if &numerator_root.pow(degree) != numerator || &denominator_root.pow(degree) != denominator {
+            return Err(NumberError::Inexact);
+        }
+        Ok(Self(BigRational::new(numerator_root, denominator_root)))
+    }
+
+    /// A fraction without a finite decimal expansion, such as `1/3`, is written as is.
+    fn to_scientific(&self) -> String {
+        BigDecimal::from(self.0.numer().clone())
+            .divide(&BigDecimal::from(self.0.denom().clone()))
+            .map_or_else(|_| self.to_string(), |decimal| decimal.to_scientific())
+    }
+
+    fn from_f64(_: f64) -> Result<Self, NumberError> {
+        Err(NumberError::Inexact)
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    fn to_integer(&self) -> Option<BigInt> {
+        self.0.is_integer().then(|| self.0.to_integer())
+    }
+
+    fn from_integer(integer: BigInt) -> Result<Self, NumberError> {
+        Ok(Rational(BigRational::from_integer(integer)))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+/// Parses decimal literals such as `0.25`, which become the fraction `1/4`.
+impl FromStr for Rational {
+    type Err = ParseBigDecimalError;
+
+    fn from_str(number: &str) -> Result<Self, Self::Err> {
+        let (digits, scale) = number.parse::<BigDecimal>()?.into_bigint_and_exponent();
+        let exponent = u32::try_from(scale.unsigned_abs())
+            .map_err(|_| ParseBigDecimalError::Other("Exponent out of range".to_string()))?;
+        let power = BigInt::from(10).pow(exponent);
+        if scale.is_negative() {
+            return Ok(Self(BigRational::from_integer(digits * power)));
+        }
+        Ok(Self(BigRational::new(digits, power)))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod rational_tests {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> Rational {
+        Rational::new(BigInt::from(numerator), BigInt::from(denominator)).unwrap()
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(Rational::new(BigInt::from(2), BigInt::from(-4)), Ok(rational(-1, 2)));
+        assert_eq!(Rational::new(BigInt::from(1), BigInt::from(0)), Err(NumberError::DivisionByZero));
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("3".parse(), Ok(rational(3, 1)));
+        assert_eq!("0.25".parse(), Ok(rational(1, 4)));
+        assert_eq!("-1.5".parse(), Ok(rational(-3, 2)));
+        assert_eq!("1e3".parse(), Ok(rational(1000, 1)));
+        assert!("1/2".parse::<Rational>().is_err());
+        assert!("1e-9999999999".parse::<Rational>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(rational(2, 4).to_string(), "1/2");
+        assert_eq!(rational(-6, 3).to_string(), "-2");
+        assert_eq!(rational(1, -3).to_string(), "-1/3");
+    }
+
+    #[test]
+    fn exact_arithmetic() {
+        assert_eq!(rational(1, 3).add(&rational(1, 6)), Ok(rational(1, 2)));
+        assert_eq!(rational(1, 3).subtract(&rational(1, 2)), Ok(rational(-1, 6)));
+        assert_eq!(rational(2, 3).multiply(&rational(3, 4)), Ok(rational(1, 2)));
+        assert_eq!(rational(1, 3).divide(&rational(2, 1)), Ok(rational(1, 6)));
+        assert_eq!(rational(7, 2).remainder(&rational(1, 1)), Ok(rational(1, 2)));
+        assert_eq!(rational(1, 3).divide(&rational(0, 1)), Err(NumberError::DivisionByZero));
+        assert_eq!(rational(1, 3).remainder(&rational(0, 1)), Err(NumberError::DivisionByZero));
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(rational(2, 3).power(&rational(2, 1)), Ok(rational(4, 9)));
+        assert_eq!(rational(2, 3).power(&rational(-1, 1)), Ok(rational(3, 2)));
+        assert_eq!(rational(4, 1).power(&rational(1, 2)), Err(NumberError::Inexact));
+        assert_eq!(rational(0, 1).power(&rational(-1, 1)), Err(NumberError::DivisionByZero));
+        assert_eq!(rational(1, 1).power(&rational(2000000000, 1)), Ok(rational(1, 1)));
+        assert_eq!(rational(9, 1).power(&rational(387420489, 1)), Err(NumberError::OutOfRange));
+    }
+
+    #[test]
+    fn root() {
+        assert_eq!(rational(4, 9).root(2), Ok(rational(2, 3)));
+        assert_eq!(rational(-1, 8).root(3), Ok(rational(-1, 2)));
+        assert_eq!(rational(0, 1).root(2), Ok(rational(0, 1)));
+        assert_eq!(rational(2, 1).root(2), Err(NumberError::Inexact));
+        assert_eq!(rational(4, 3).root(2), Err(NumberError::Inexact));
+        assert_eq!(rational(-4, 1).root(2), Err(NumberError::OutOfRange));
+    }
+
+    #[test]
+    fn to_scientific() {
+        assert_eq!(rational(-5, 4).to_scientific(), "-1.25e0");
+        assert_eq!(rational(1, 3).to_scientific(), "1/3");
+        assert_eq!("1e400".parse::<Rational>().unwrap().to_scientific(), "1e400");
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(Number::floor(&rational(-3, 2)), rational(-2, 1));
+        assert_eq!(Number::ceil(&rational(-3, 2)), rational(-1, 1));
+        assert_eq!(Number::round(&rational(5, 2)), rational(3, 1));
+        assert_eq!(Number::abs(&rational(-1, 3)), rational(1, 3));
+        assert_eq!(rational(1, 4).to_f64(), 0.25);
+        assert_eq!(rational(-4, 2).to_integer(), Some(BigInt::from(-2)));
+        assert_eq!(rational(1, 2).to_integer(), None);
+        assert_eq!(Rational::from_f64(0.5), Err(NumberError::Inexact));
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use console_calculator::{
+    parse_in, AngleUnit, AngleUnitError, EvalContext, EvalError, Evaluate, ExpressionBuilderError,
+    Number, OutputFormat, Value,
+};
+
+const PROMPT: &str = "> ";
+const HISTORY_FILE: &str = ".calc_history";
+const HELP: &str = "Enter an equation to evaluate it, e.g. 2+2*(3-1).
+Operators: ^ * / % + -, multiplication can be implied as in 2(3+4), (1+2)3 or 3x.
+Integer operators: // (floor division), & | xor ~ << >> and ! (factorial).
+Conditions: < <= == != > >= combined with && || and negated with !, as in !(x < 0 || x > 1).
+Choose between two values with condition ? value : otherwise, e.g. x != 0 ? 1/x : 0.
+Assign a variable with x = 3*4 and use it in later equations.
+Define a function with f(x, y) = x^2+y and call it with f(1, 2).
+Separate several statements on one line with ;, as in x = 2; x^2.
+Functions: sin cos tan asin acos atan atan2 sinh cosh tanh sqrt cbrt exp ln log abs
+           floor ceil round min max
+With --decimal or --rational only sqrt, cbrt, abs, floor, ceil, round, min and max are exact.
+Constants: pi e
+Numbers: 1_000, 6.02e23, 0x1F, 0b1010, 0o17
+Angles are in radians unless set with --angle or :angle, a unit written directly after a number
+overrides it, as in sin(90deg), (pi/2)rad or 100grad.
+With --strict, division by zero, overflow and NaN are errors instead of inf and NaN.
+
+Commands:
+  :help                    Show this message
+  :angle [deg|rad|grad]    Show or set the unit of angles
+  :quit                    Exit the calculator";
+
+#[derive(Debug, PartialEq)]
+pub enum MetaCommand {
+    Help,
+    Quit,
+    /// Sets the unit of angles, or shows it without one.
+    Angle(Option<AngleUnit>),
+}
+
+impl MetaCommand {
+    pub fn new(command: &str) -> Result<Self, MetaCommandError> {
+        let (command, argument) = match command.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, Some(argument.trim())),
+            None => (command, None),
+        };
+        match (command, argument) {
+            (":help" | ":h", None) => Ok(MetaCommand::Help),
+            (":quit" | ":q" | ":exit", None) => Ok(MetaCommand::Quit),
+            (":angle", None) => Ok(MetaCommand::Angle(None)),
+            (":angle", Some(unit)) => Ok(MetaCommand::Angle(Some(unit.parse()?))),
+            (command, _) => Err(MetaCommandError::UnknownCommand(command.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MetaCommandError {
+    UnknownCommand(String),
+    AngleUnitError(AngleUnitError),
+}
+
+impl Display for MetaCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetaCommandError::UnknownCommand(command) => {
+                write!(f, "Unknown command {}, type :help for a list of commands.", command)
+            }
+            MetaCommandError::AngleUnitError(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for MetaCommandError {}
+
+impl From<AngleUnitError> for MetaCommandError {
+    fn from(err: AngleUnitError) -> Self {
+        MetaCommandError::AngleUnitError(err)
+    }
+}
+
+fn history_path() -> PathBuf {
+    std::env::var_os("HOME")
+        .map_or_else(PathBuf::new, PathBuf::from)
+        .join(HISTORY_FILE)
+}
+
+fn print_error(line: &str, err: &EvalError) {
+    println!("{}", err);
+    println!("{}", err.span().underline(line));
+}
+
+fn print_result<N: Number>(
+    line: &str,
+    result: &Result<Option<Value<N>>, EvalError>,
+    format: OutputFormat,
+) {
+    match result {
+        Ok(Some(result)) => println!("{}", format.format_value(result)),
+        Ok(None) => {}
+        Err(err) => print_error(line, err),
+    }
+}
+
+/// Prints every syntax error in the line, or else evaluates its statements in order, printing
+/// each result, until one fails.
+fn run_line<N: Number>(line: &str, context: &mut EvalContext<N>, format: OutputFormat) {
+    match parse_in(line, context) {
+        Ok(statements) => {
+            for statement in statements {
+                let result = statement.eval(context);
+                print_result(line, &result, format);
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+        Err(errors) => {
+            for err in errors {
+                print_error(line, &err.into());
+            }
+        }
+    }
+}
+
+/// Prints every syntax error in the equation, or else evaluates its statements in order and prints
+/// the value of the last one, stopping at the first error.
+pub fn run_equation<N: Number>(equation: &str, context: &mut EvalContext<N>, format: OutputFormat) {
+    match parse_in(equation, context) {
+        Ok(statements) => {
+            let mut result = Ok(None);
+            for statement in statements {
+                result = statement.eval(context);
+                if result.is_err() {
+                    break;
+                }
+            }
+            print_result(equation, &result, format);
+        }
+        Err(errors) => {
+            for err in errors {
+                print_error(equation, &err.into());
+            }
+        }
+    }
+}
+
+fn print_line_errors(index: usize, line: &str, errors: Vec<ExpressionBuilderError>) {
+    for err in errors {
+        print!("Line {}: ", index + 1);
+        print_error(line, &err.into());
+    }
+}
+
+/// Evaluates a file line by line in a shared context, stopping at the first error.
+/// Blank lines and lines starting with `#` are skipped. The whole file is parsed first, so every
+/// syntax error is reported before anything is evaluated. Each line is read again just before it
+/// is evaluated, so that `x(2)` is a product when an earlier line assigned `x`.
+pub fn run_file<N: Number>(
+    path: &Path,
+    mut context: EvalContext<N>,
+    format: OutputFormat,
+) -> std::io::Result<()> {
+    let source = std::fs::read_to_string(path)?;
+    let mut lines = Vec::new();
+    let mut valid = true;
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_in(line, &context) {
+            Ok(_) => lines.push((index, line)),
+            Err(errors) => {
+                valid = false;
+                print_line_errors(index, line, errors);
+            }
+        }
+    }
+    if !valid {
+        return Ok(());
+    }
+    for (index, line) in lines {
+        let statements = match parse_in(line, &context) {
+            Ok(statements) => statements,
+            Err(errors) => {
+                print_line_errors(index, line, errors);
+                return Ok(());
+            }
+        };
+        for statement in statements {
+            let result = statement.eval(&mut context);
+            if result.is_err() {
+                print!("Line {}: ", index + 1);
+            }
+            print_result(line, &result, format);
+            if result.is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run<N: Number>(mut context: EvalContext<N>, format: OutputFormat) -> rustyline::Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    let history_path = history_path();
+    // A missing history file just means this is the first session.
+    let _ = editor.load_history(&history_path);
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line)?;
+        if line.starts_with(':') {
+            match MetaCommand::new(line) {
+                Ok(MetaCommand::Help) => println!("{}", HELP),
+                Ok(MetaCommand::Quit) => break,
+                Ok(MetaCommand::Angle(Some(unit))) => context.set_angle_unit(unit),
+                Ok(MetaCommand::Angle(None)) => println!("{}", context.angle_unit()),
+                Err(err) => println!("{}", err),
+            }
+            continue;
+        }
+        run_line(line, &mut context, format);
+    }
+    editor.save_history(&history_path)
+}
+
+#[cfg(test)]
+mod repl_tests {
+    use super::*;
+
+    #[test]
+    fn meta_command() {
+        for command in [":help", ":h"] {
+            assert!(MetaCommand::new(command).is_ok_and(|ok| ok == MetaCommand::Help));
+        }
+        for command in [":quit", ":q", ":exit"] {
+            assert!(MetaCommand::new(command).is_ok_and(|ok| ok == MetaCommand::Quit));
+        }
+        assert!(MetaCommand::new(":foo")
+            .is_err_and(|err| err == MetaCommandError::UnknownCommand(":foo".to_string())));
+        assert!(MetaCommand::new(":angle").is_ok_and(|ok| ok == MetaCommand::Angle(None)));
+        assert!(MetaCommand::new(":angle  deg")
+            .is_ok_and(|ok| ok == MetaCommand::Angle(Some(AngleUnit::Degrees))));
+        assert!(MetaCommand::new(":angle turn").is_err_and(|err| err
+            == MetaCommandError::AngleUnitError(AngleUnitError::UnknownUnit("turn".to_string()))));
+        assert!(MetaCommand::new(":help me")
+            .is_err_and(|err| err == MetaCommandError::UnknownCommand(":help".to_string())));
+    }
+}
@@ -0,0 +1,41 @@
+/// Byte offsets into the source, `end` being exclusive.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn underline(&self, source: &str) -> String {
+        // Offsets past the end of the source count as one column per byte.
+        let column = |offset: usize| match source.get(..offset) {
+            Some(prefix) => prefix.chars().count(),
+            None => source.chars().count() + offset.saturating_sub(source.len()),
+        };
+        let (start, end) = (column(self.start), column(self.end));
+        format!(
+            "{}\n{}{}",
+            source,
+            " ".repeat(start),
+            "^".repeat(end.saturating_sub(start).max(1))
+        )
+    }
+}
+
+#[cfg(test)]
+mod span_tests {
+    use super::*;
+
+    #[test]
+    fn underline() {
+        assert_eq!(Span::new(2, 3).underline("1+)"), "1+)\n  ^");
+        assert_eq!(Span::new(0, 3).underline("(1+"), "(1+\n^^^");
+        assert_eq!(Span::new(2, 2).underline("1+"), "1+\n  ^");
+        assert_eq!(Span::new(3, 4).underline("é+)"), "é+)\n  ^");
+        assert_eq!(Span::new(4, 5).underline("é+1"), "é+1\n   ^");
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use crate::expression::Expression;
+
+/// A parsed statement, ready to be evaluated with `Evaluate` or written back with `Display`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Statement<N = f64> {
+    pub(crate) kind: StatementKind<N>,
+}
+
+impl<N> Statement<N> {
+    pub(crate) fn new(kind: StatementKind<N>) -> Self {
+        Self { kind }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum StatementKind<N = f64> {
+    Expression(Expression<N>),
+    Assignment(String, Expression<N>),
+    Definition(String, Vec<String>, Expression<N>),
+}
+
+impl<N: Display> Display for Statement<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            StatementKind::Expression(expression) => write!(f, "{}", expression),
+            StatementKind::Assignment(identifier, expression) => {
+                write!(f, "{}={}", identifier, expression)
+            }
+            StatementKind::Definition(identifier, parameters, expression) => {
+                write!(f, "{}({})={}", identifier, parameters.join(","), expression)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod statement_tests {
+    use super::*;
+    use crate::{expression::ExpressionKind, operator::Operator, span::Span};
+
+    #[test]
+    fn display() {
+        let expression = Expression::binary(
+            Expression::new(ExpressionKind::Literal(1.0), Span::new(0, 1)),
+            Operator::Add,
+            Span::new(1, 2),
+            Expression::new(ExpressionKind::Variable("y".to_string()), Span::new(2, 3)),
+        );
+        assert_eq!(
+            format!("{}", Statement::new(StatementKind::Expression(expression.clone()))),
+            "(1+y)"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                Statement::new(StatementKind::Assignment("x".to_string(), expression.clone()))
+            ),
+            "x=(1+y)"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                Statement::new(StatementKind::Definition(
+                    "f".to_string(),
+                    vec!["x".to_string(), "y".to_string()],
+                    expression
+                ))
+            ),
+            "f(x,y)=(1+y)"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt::Display;
+
+/// Result of evaluating an expression: a number, or the outcome of a comparison such as `x < 2`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value<N = f64> {
+    Number(N),
+    Boolean(bool),
+}
+
+impl<N> Value<N> {
+    pub fn number(self) -> Option<N> {
+        match self {
+            Value::Number(number) => Some(number),
+            Value::Boolean(_) => None,
+        }
+    }
+
+    pub fn boolean(&self) -> Option<bool> {
+        match self {
+            Value::Number(_) => None,
+            Value::Boolean(boolean) => Some(*boolean),
+        }
+    }
+}
+
+/// Lets a value be compared with a plain number, `Value::Number(2.0) == 2.0`.
+impl<N: PartialEq> PartialEq<N> for Value<N> {
+    fn eq(&self, other: &N) -> bool {
+        matches!(self, Value::Number(number) if number == other)
+    }
+}
+
+impl<N: Display> Display for Value<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+        }
+    }
+}
+
+#[cfg(test)]
+mod value_tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Value::Number(1.5).number(), Some(1.5));
+        assert_eq!(Value::<f64>::Boolean(true).number(), None);
+        assert_eq!(Value::<f64>::Boolean(false).boolean(), Some(false));
+        assert_eq!(Value::Number(1.5).boolean(), None);
+        assert!(Value::Number(1.5) == 1.5);
+        assert!(Value::<f64>::Boolean(true) != 1.0);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Value::Number(1.5).to_string(), "1.5");
+        assert_eq!(Value::<f64>::Boolean(true).to_string(), "true");
+    }
+}
@@ -0,0 +1,74 @@
+use bigdecimal::BigDecimal;
+use console_calculator::{
+    evaluate, parse, parse_in, AngleUnit, EvalContext, EvalError, ExpressionBuilderError,
+    FloatPolicy, NumberError, Rational, Span, Statement, Value,
+};
+
+#[test]
+fn parse_statements() {
+    assert!(parse::<f64>("x = 2; f(y) = x*y; f(3)").is_ok_and(|statements| statements
+        .iter()
+        .map(Statement::to_string)
+        .eq(["x=2", "f(y)=(x*y)", "f(3)"])));
+    assert_eq!(
+        parse::<f64>("1+; (2"),
+        Err(vec![
+            ExpressionBuilderError::ExpectedOperand(Span::new(2, 3)),
+            ExpressionBuilderError::ExpectedClosingParentheses(Span::new(4, 6)),
+        ])
+    );
+    let mut context = EvalContext::<f64>::new();
+    context.set("x", 3.0);
+    assert!(parse::<f64>("x(2)").is_ok_and(|statements| statements[0].to_string() == "x(2)"));
+    assert!(parse_in("x(2)", &context).is_ok_and(|statements| statements[0].to_string() == "(x*2)"));
+}
+
+#[test]
+fn evaluate_in_context() {
+    let mut context = EvalContext::new();
+    assert!(evaluate("x = 3", &mut context).is_ok_and(|ok| ok == Some(Value::Number(3.0))));
+    assert!(evaluate("f(y) = x*y", &mut context).is_ok_and(|ok| ok.is_none()));
+    assert!(evaluate("f(2) > 5", &mut context).is_ok_and(|ok| ok == Some(Value::Boolean(true))));
+    assert_eq!(context.get("x"), Some(3.0));
+    context.set("x", 4.0);
+    assert!(evaluate("f(2)", &mut context).is_ok_and(|ok| ok == Some(Value::Number(8.0))));
+    assert!(evaluate("y", &mut context)
+        .is_err_and(|err| err == EvalError::UndefinedVariable("y".to_string(), Span::new(0, 1))));
+    assert!(evaluate("2*(3", &mut context).is_err_and(|err| err.span() == Span::new(2, 4)));
+}
+
+#[test]
+fn evaluate_number_types() {
+    let mut context = EvalContext::<BigDecimal>::new();
+    assert!(evaluate("0.1 + 0.2", &mut context)
+        .is_ok_and(|ok| ok == Some(Value::Number("0.3".parse().unwrap()))));
+    assert!(evaluate("1/3", &mut context)
+        .is_err_and(|err| err == EvalError::NumberError(NumberError::Inexact, Span::new(1, 2))));
+    let mut context = EvalContext::<Rational>::new();
+    assert!(evaluate("1/3 + 1/6", &mut context)
+        .is_ok_and(|ok| ok.is_some_and(|value| value.to_string() == "1/2")));
+}
+
+#[test]
+fn evaluate_strict() {
+    let mut context = EvalContext::<f64>::new();
+    assert!(evaluate("1/0", &mut context).is_ok_and(|ok| ok == Some(Value::Number(f64::INFINITY))));
+    context.set_policy(FloatPolicy::Strict);
+    assert!(evaluate("1/0", &mut context)
+        .is_err_and(|err| err == EvalError::DivisionByZero(Span::new(1, 2))));
+}
+
+#[test]
+fn evaluate_configured() {
+    let mut context = EvalContext::<f64>::new();
+    context.set_angle_unit(AngleUnit::Gradians);
+    assert!(evaluate("cos(200)", &mut context).is_ok_and(|ok| ok == Some(Value::Number(-1.0))));
+    assert!(evaluate("cos(180deg)", &mut context).is_ok_and(|ok| ok == Some(Value::Number(-1.0))));
+    context.set_max_call_depth(1);
+    assert!(evaluate("f(x) = x; g(x) = f(x); g(1)", &mut context).is_err_and(|err| err
+        == EvalError::RecursionLimit {
+            function: "f".to_string(),
+            limit: 1,
+            span: Span::new(23, 27),
+        }));
+}